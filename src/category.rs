@@ -0,0 +1,76 @@
+use eframe::epaint::Color32;
+use serde::{Deserialize, Serialize};
+
+/// Stable identifier of a category within its timeline, so renaming it doesn't orphan events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CategoryId(pub u32);
+
+/// A named group of events sharing a colour, e.g. "Work" or "Travel".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Category {
+    pub id: CategoryId,
+    pub name: String,
+    /// sRGB
+    pub color: [u8; 3],
+}
+
+impl Category {
+    pub fn color32(&self) -> Color32 {
+        Color32::from_rgb(self.color[0], self.color[1], self.color[2])
+    }
+}
+
+/// The palette new timelines start with.
+pub fn default_palette() -> Vec<Category> {
+    [
+        ("Work", [90, 160, 255]),
+        ("Family", [255, 120, 160]),
+        ("Travel", [80, 220, 170]),
+        ("Health", [255, 110, 80]),
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(i, (name, color))| Category {
+        id: CategoryId(i as u32 + 1),
+        name: name.to_string(),
+        color,
+    })
+    .collect()
+}
+
+/// An ID higher than any in `in_use`, which must include the IDs still on events: removing
+/// a category leaves them there, and a new category reusing one would claim those events.
+/// `None` once IDs run out, which only a hand-edited file can cause.
+pub fn next_id(in_use: impl IntoIterator<Item = CategoryId>) -> Option<CategoryId> {
+    match in_use.into_iter().map(|id| id.0).max() {
+        Some(max) => max.checked_add(1).map(CategoryId),
+        None => Some(CategoryId(1)),
+    }
+}
+
+/// Splits comma-separated tags, dropping blanks and repeats (ignoring case).
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_id_skips_every_id_in_use() {
+        assert_eq!(next_id([]), Some(CategoryId(1)));
+        assert_eq!(
+            next_id([CategoryId(2), CategoryId(7), CategoryId(3)]),
+            Some(CategoryId(8))
+        );
+        assert_eq!(next_id([CategoryId(1), CategoryId(u32::MAX)]), None);
+    }
+}
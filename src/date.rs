@@ -0,0 +1,503 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(target_arch = "wasm32")]
+use web_time::{SystemTime, UNIX_EPOCH};
+
+/// A calendar date in the proleptic Gregorian calendar.
+///
+/// Years use astronomical numbering as in ISO 8601: year 0 is 1 BCE, -1 is 2 BCE and so on.
+/// They are wide enough for geological and cosmological timelines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i64,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    pub fn new(year: i64, month: u8, day: u8) -> Self {
+        Self { year, month, day }
+    }
+
+    /// Today's date in UTC.
+    pub fn today() -> Self {
+        Self::from_days_since_epoch(now_seconds().div_euclid(SECONDS_PER_DAY))
+    }
+
+    /// Number of days since 1970-01-01 (negative for earlier dates).
+    pub fn days_since_epoch(&self) -> i64 {
+        // Howard Hinnant's days_from_civil, with March as the first month of the year
+        let month = self.month.clamp(1, 12) as i64;
+        let day = self.day.max(1) as i64;
+        let year = self.year - if month <= 2 { 1 } else { 0 };
+
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let shifted_month = (month + 9) % 12;
+        let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// Inverse of [`Date::days_since_epoch`].
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Self::new(year, month as u8, day as u8)
+    }
+
+    /// Whether the date exists and its year is within [`MAX_YEAR_MAGNITUDE`].
+    pub fn is_valid(&self) -> bool {
+        self.year.abs() <= MAX_YEAR_MAGNITUDE
+            && (1..=12).contains(&self.month)
+            && (1..=Self::days_in_month(self.year, self.month)).contains(&self.day)
+    }
+
+    pub fn is_leap_year(year: i64) -> bool {
+        (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+    }
+
+    pub fn days_in_month(year: i64, month: u8) -> u8 {
+        match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if Self::is_leap_year(year) => 29,
+            2 => 28,
+            _ => 0,
+        }
+    }
+}
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Current time in seconds since the Unix epoch.
+pub fn now_seconds() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Largest year magnitude accepted, so timestamps in seconds stay well inside `i64`.
+pub const MAX_YEAR_MAGNITUDE: i64 = 100_000_000_000;
+
+/// Reference year for "years ago" units, following the geological "before present" convention.
+const PRESENT_YEAR: i64 = 1950;
+
+/// Parses a year: a plain (astronomical) number, `N BCE`/`N BC`, `N CE`/`N AD`, or years
+/// before present as `N ka`, `N Ma` or `N Ga` (decimals allowed, e.g. `13.8 Ga`).
+pub fn parse_year(text: &str) -> Option<i64> {
+    let text = text.trim();
+    let (number, suffix) = match text.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(index) => (text[..index].trim(), text[index..].trim().to_uppercase()),
+        None => (text, String::new()),
+    };
+
+    let year = match suffix.as_str() {
+        "" => number.parse::<i64>().ok()?,
+        "CE" | "AD" => number.parse::<i64>().ok().filter(|y| *y >= 1)?,
+        // There is no year 0 in BCE/CE notation: 1 BCE is astronomical year 0
+        "BCE" | "BC" => 1 - number.parse::<i64>().ok().filter(|y| *y >= 1)?,
+        "KA" | "MA" | "GA" => {
+            let scale = match suffix.as_str() {
+                "KA" => 1e3,
+                "MA" => 1e6,
+                _ => 1e9,
+            };
+            let years_ago = number.parse::<f64>().ok().filter(|y| y.is_finite())? * scale;
+            if years_ago.abs() > MAX_YEAR_MAGNITUDE as f64 {
+                return None;
+            }
+            PRESENT_YEAR - years_ago.round() as i64
+        }
+        _ => return None,
+    };
+
+    (year.abs() <= MAX_YEAR_MAGNITUDE).then_some(year)
+}
+
+/// Formats a year for display: plain for recent CE years, BCE/CE around antiquity, and
+/// ka/Ma/Ga (years before present) for deep time.
+pub fn format_year(year: i64) -> String {
+    let years_ago = PRESENT_YEAR - year;
+    if years_ago >= 1_000_000_000 {
+        format!("{} Ga", trim_decimal(years_ago as f64 / 1e9))
+    } else if years_ago >= 1_000_000 {
+        format!("{} Ma", trim_decimal(years_ago as f64 / 1e6))
+    } else if years_ago >= 10_000 {
+        format!("{} ka", trim_decimal(years_ago as f64 / 1e3))
+    } else if year <= 0 {
+        format!("{} BCE", 1 - year)
+    } else if year < 1000 {
+        format!("{} CE", year)
+    } else {
+        year.to_string()
+    }
+}
+
+/// Up to two decimals, without trailing zeros: 13.8, 66, 2.55.
+fn trim_decimal(value: f64) -> String {
+    let text = format!("{:.2}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// How much of a date is actually known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatePrecision {
+    Year,
+    /// The month holds the season's first month: 3, 6, 9 or 12
+    Season,
+    Month,
+    #[default]
+    Day,
+    Time,
+}
+
+impl DatePrecision {
+    /// Days since the epoch of the first day covered and of the day after the last one.
+    pub fn day_range(self, date: Date) -> (i64, i64) {
+        let months = match self {
+            DatePrecision::Year => {
+                return (
+                    Date::new(date.year, 1, 1).days_since_epoch(),
+                    Date::new(date.year + 1, 1, 1).days_since_epoch(),
+                );
+            }
+            DatePrecision::Season => 3,
+            DatePrecision::Month => 1,
+            DatePrecision::Day | DatePrecision::Time => {
+                let day = date.days_since_epoch();
+                return (day, day + 1);
+            }
+        };
+
+        let next_month = date.month as i64 - 1 + months;
+        let next = Date::new(
+            date.year + next_month.div_euclid(12),
+            (next_month.rem_euclid(12) + 1) as u8,
+            1,
+        );
+        (
+            Date::new(date.year, date.month, 1).days_since_epoch(),
+            next.days_since_epoch(),
+        )
+    }
+}
+
+/// Parses a season name into the first month of that (northern hemisphere) season.
+pub fn season_start_month(name: &str) -> Option<u8> {
+    match name.trim().to_lowercase().as_str() {
+        "spring" => Some(3),
+        "summer" => Some(6),
+        "autumn" | "fall" => Some(9),
+        "winter" => Some(12),
+        _ => None,
+    }
+}
+
+pub fn season_name(start_month: u8) -> &'static str {
+    match start_month {
+        3..=5 => "Spring",
+        6..=8 => "Summer",
+        9..=11 => "Autumn",
+        _ => "Winter",
+    }
+}
+
+/// A wall-clock time within a day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimeOfDay {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl TimeOfDay {
+    pub fn new(hour: u8, minute: u8, second: u8) -> Option<Self> {
+        if hour < 24 && minute < 60 && second < 60 {
+            Some(Self {
+                hour,
+                minute,
+                second,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Parses `HH:MM` or `HH:MM:SS`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.trim().split(':');
+        let hour = parts.next()?.trim().parse().ok()?;
+        let minute = parts.next()?.trim().parse().ok()?;
+        let second = match parts.next() {
+            Some(second) => second.trim().parse().ok()?,
+            None => 0,
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+        Self::new(hour, minute, second)
+    }
+
+    pub fn seconds_from_midnight(&self) -> i64 {
+        self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.second == 0 {
+            write!(f, "{:02}:{:02}", self.hour, self.minute)
+        } else {
+            write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)
+        }
+    }
+}
+
+/// Either a fixed UTC offset or an IANA zone such as `Europe/Oslo`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeZone {
+    /// Minutes east of UTC
+    Offset(i32),
+    Named(chrono_tz::Tz),
+}
+
+impl TimeZone {
+    /// Parses `UTC`, `Z`, `+02:00`, `UTC-05:30`, `+2` or an IANA zone name.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if let Ok(tz) = text.parse::<chrono_tz::Tz>() {
+            return Some(TimeZone::Named(tz));
+        }
+
+        let offset = text
+            .strip_prefix("UTC")
+            .or_else(|| text.strip_prefix("GMT"))
+            .unwrap_or(text)
+            .trim();
+        if offset.is_empty() || offset == "Z" {
+            return Some(TimeZone::Offset(0));
+        }
+
+        let (sign, rest) = match offset.as_bytes()[0] {
+            b'+' => (1, &offset[1..]),
+            b'-' => (-1, &offset[1..]),
+            _ => return None,
+        };
+        // Plain digits only, so no second sign sneaks in (as in "+-3" or "+02:-5")
+        let number = |digits: &str| {
+            let valid =
+                (1..=2).contains(&digits.len()) && digits.bytes().all(|b| b.is_ascii_digit());
+            valid.then(|| digits.parse::<i32>().ok()).flatten()
+        };
+        let (hours, minutes) = match rest.split_once(':') {
+            Some((hours, minutes)) => (number(hours)?, number(minutes)?),
+            None if rest.len() == 4 => (number(rest.get(..2)?)?, number(rest.get(2..)?)?),
+            None => (number(rest)?, 0),
+        };
+        if hours > 14 || minutes >= 60 {
+            return None;
+        }
+        Some(TimeZone::Offset(sign * (hours * 60 + minutes)))
+    }
+
+    /// Seconds to add to UTC to get local time at the given local date and time.
+    pub fn offset_seconds(&self, date: Date, time: TimeOfDay) -> i64 {
+        match self {
+            TimeZone::Offset(minutes) => *minutes as i64 * 60,
+            TimeZone::Named(tz) => {
+                use chrono::{Offset, TimeZone as _};
+
+                let Some(local) = i32::try_from(date.year)
+                    .ok()
+                    .and_then(|year| {
+                        chrono::NaiveDate::from_ymd_opt(year, date.month as u32, date.day as u32)
+                    })
+                    .and_then(|d| {
+                        d.and_hms_opt(time.hour as u32, time.minute as u32, time.second as u32)
+                    })
+                else {
+                    return 0;
+                };
+
+                // Times skipped by a DST jump fall back to the offset in effect at that UTC instant
+                match tz.offset_from_local_datetime(&local).earliest() {
+                    Some(offset) => offset.fix().local_minus_utc() as i64,
+                    None => tz.offset_from_utc_datetime(&local).fix().local_minus_utc() as i64,
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for TimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeZone::Offset(0) => write!(f, "UTC"),
+            TimeZone::Offset(minutes) => {
+                let sign = if *minutes < 0 { '-' } else { '+' };
+                let minutes = minutes.abs();
+                write!(f, "UTC{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+            }
+            TimeZone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+/// Seconds since 1970-01-01T00:00:00Z. Events without a time of day sit at local midnight.
+pub fn instant_seconds(date: Date, time: Option<TimeOfDay>, timezone: Option<&TimeZone>) -> i64 {
+    let time = time.unwrap_or(TimeOfDay {
+        hour: 0,
+        minute: 0,
+        second: 0,
+    });
+    let offset = timezone.map_or(0, |tz| tz.offset_seconds(date, time));
+    date.days_since_epoch() * SECONDS_PER_DAY + time.seconds_from_midnight() - offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(year: i64, month: u8, day: u8) -> i64 {
+        let date = Date::new(year, month, day);
+        let days = date.days_since_epoch();
+        assert_eq!(Date::from_days_since_epoch(days), date);
+        days
+    }
+
+    #[test]
+    fn days_since_epoch_around_1970() {
+        assert_eq!(round_trip(1970, 1, 1), 0);
+        assert_eq!(round_trip(1969, 12, 31), -1);
+        assert_eq!(round_trip(1970, 1, 2), 1);
+        assert_eq!(round_trip(2000, 3, 1), 11_017);
+    }
+
+    #[test]
+    fn leap_days() {
+        // 1900 is not a leap year, 2000 and 2024 are
+        assert_eq!(round_trip(1900, 3, 1) - round_trip(1900, 2, 28), 1);
+        assert_eq!(round_trip(2000, 3, 1) - round_trip(2000, 2, 29), 1);
+        assert_eq!(round_trip(2024, 3, 1) - round_trip(2024, 2, 29), 1);
+        assert_eq!(round_trip(2000, 2, 29) - round_trip(2000, 2, 28), 1);
+        assert_eq!(Date::days_in_month(1900, 2), 28);
+        assert_eq!(Date::days_in_month(2000, 2), 29);
+        assert_eq!(Date::days_in_month(2024, 2), 29);
+    }
+
+    #[test]
+    fn years_before_year_one() {
+        // Astronomical year 0 (1 BCE) is a leap year
+        assert_eq!(round_trip(1, 1, 1) - round_trip(0, 12, 31), 1);
+        assert_eq!(round_trip(0, 3, 1) - round_trip(0, 2, 29), 1);
+        assert_eq!(round_trip(-1, 3, 1) - round_trip(-1, 2, 28), 1);
+        round_trip(-4712, 1, 1);
+        round_trip(-MAX_YEAR_MAGNITUDE, 6, 15);
+        round_trip(MAX_YEAR_MAGNITUDE, 6, 15);
+    }
+
+    #[test]
+    fn every_day_of_a_400_year_cycle_round_trips() {
+        let start = Date::new(1600, 1, 1).days_since_epoch();
+        for days in start..start + 146_097 {
+            let date = Date::from_days_since_epoch(days);
+            assert_eq!(date.days_since_epoch(), days);
+        }
+    }
+
+    #[test]
+    fn parses_time_zones() {
+        assert_eq!(TimeZone::parse("Z"), Some(TimeZone::Offset(0)));
+        assert_eq!(TimeZone::parse("+02:00"), Some(TimeZone::Offset(120)));
+        assert_eq!(TimeZone::parse("UTC-05:30"), Some(TimeZone::Offset(-330)));
+        assert_eq!(TimeZone::parse("+0530"), Some(TimeZone::Offset(330)));
+        assert_eq!(TimeZone::parse("+2"), Some(TimeZone::Offset(120)));
+        assert_eq!(
+            TimeZone::parse("Europe/Oslo"),
+            Some(TimeZone::Named(chrono_tz::Europe::Oslo))
+        );
+
+        for invalid in [
+            "+-3",
+            "+02:-5",
+            "+02:60",
+            "+15",
+            "+",
+            "02:00",
+            "Mars/Olympus",
+            "+é",
+        ] {
+            assert_eq!(TimeZone::parse(invalid), None, "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn instants_respect_the_time_zone() {
+        let date = Date::new(2024, 7, 1);
+        let noon = TimeOfDay::parse("12:00");
+        let utc = instant_seconds(date, noon, None);
+        assert_eq!(
+            instant_seconds(date, noon, TimeZone::parse("+02:00").as_ref()),
+            utc - 2 * 3600
+        );
+        // Central European Summer Time in July
+        assert_eq!(
+            instant_seconds(date, noon, TimeZone::parse("Europe/Oslo").as_ref()),
+            utc - 2 * 3600
+        );
+    }
+
+    #[test]
+    fn parses_years() {
+        assert_eq!(parse_year("1990"), Some(1990));
+        assert_eq!(parse_year("-43"), Some(-43));
+        assert_eq!(parse_year("44 BCE"), Some(-43));
+        assert_eq!(parse_year("44 bc"), Some(-43));
+        assert_eq!(parse_year("1 BCE"), Some(0));
+        assert_eq!(parse_year("500 AD"), Some(500));
+        assert_eq!(parse_year("66 Ma"), Some(PRESENT_YEAR - 66_000_000));
+        assert_eq!(parse_year("13.8 Ga"), Some(PRESENT_YEAR - 13_800_000_000));
+        assert_eq!(parse_year("12 ka"), Some(PRESENT_YEAR - 12_000));
+
+        for invalid in ["", "0 BCE", "0 CE", "-5 BCE", "44 BCX", "NaN Ma", "1000 Ga"] {
+            assert_eq!(parse_year(invalid), None, "{:?}", invalid);
+        }
+        assert_eq!(parse_year(&(MAX_YEAR_MAGNITUDE + 1).to_string()), None);
+    }
+
+    #[test]
+    fn formats_years() {
+        assert_eq!(format_year(2024), "2024");
+        assert_eq!(format_year(800), "800 CE");
+        assert_eq!(format_year(0), "1 BCE");
+        assert_eq!(format_year(-43), "44 BCE");
+        assert_eq!(format_year(PRESENT_YEAR - 12_000), "12 ka");
+        assert_eq!(format_year(PRESENT_YEAR - 66_000_000), "66 Ma");
+        assert_eq!(format_year(PRESENT_YEAR - 13_800_000_000), "13.8 Ga");
+
+        for year in [2024, 800, 1, 0, -43, -7999, PRESENT_YEAR - 66_000_000] {
+            assert_eq!(parse_year(&format_year(year)), Some(year));
+        }
+    }
+}
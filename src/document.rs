@@ -0,0 +1,146 @@
+use crate::timeline::Timeline;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+pub const UNTITLED: &str = "Untitled";
+pub const FILE_EXTENSION: &str = "json";
+
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_RECENT_FILES: usize = 8;
+
+/// Which file the open timeline belongs to, and what it looked like when last opened or saved.
+/// Persisted next to the autosaved working copy so the association survives restarts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Document {
+    /// Always `None` on the web, where files are downloaded rather than written in place
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    pub name: String,
+    /// [`content_hash`] at the last open or save. A hash rather than the content itself, so
+    /// images aren't stored twice; missing in older state, which then reads as unsaved.
+    #[serde(default)]
+    saved_hash: u64,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl Document {
+    pub fn untitled(timeline: &Timeline) -> Self {
+        Self::saved_as(None, UNTITLED.to_string(), timeline)
+    }
+
+    pub fn saved_as(path: Option<PathBuf>, name: String, timeline: &Timeline) -> Self {
+        Self {
+            path,
+            name,
+            saved_hash: content_hash(timeline).unwrap_or_default(),
+            dirty: false,
+        }
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Compares the timeline against the last saved version.
+    pub fn refresh_dirty(&mut self, timeline: &Timeline) {
+        self.dirty = content_hash(timeline) != Some(self.saved_hash);
+    }
+
+    pub fn window_title(&self) -> String {
+        let marker = if self.dirty { "*" } else { "" };
+        format!("{}{} - Lifeline Timeline", marker, self.name)
+    }
+}
+
+/// FNV-1a hash of `Timeline::content_json`. Unlike the std hashers it's guaranteed not to
+/// change between Rust versions, so saved state stays comparable after an update.
+fn content_hash(timeline: &Timeline) -> Option<u64> {
+    let json = timeline.content_json().ok()?;
+    let hash = json.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    Some(hash)
+}
+
+#[derive(Debug)]
+pub enum DocumentError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+}
+
+impl fmt::Display for DocumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DocumentError::Io(err) => write!(f, "{}", err),
+            DocumentError::Parse(err) => write!(f, "not a valid timeline file: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for DocumentError {}
+
+impl From<std::io::Error> for DocumentError {
+    fn from(err: std::io::Error) -> Self {
+        DocumentError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for DocumentError {
+    fn from(err: serde_json::Error) -> Self {
+        DocumentError::Parse(err)
+    }
+}
+
+/// Name shown in the title bar for a document file.
+pub fn display_name(file_name: &str) -> String {
+    file_name
+        .strip_suffix(&format!(".{}", FILE_EXTENSION))
+        .unwrap_or(file_name)
+        .to_string()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn open_file(path: &Path) -> Result<(Timeline, Document), DocumentError> {
+    let json = std::fs::read_to_string(path)?;
+    let timeline = Timeline::from_json(&json)?;
+    let name = display_name(&path.file_name().unwrap_or_default().to_string_lossy());
+    let document = Document::saved_as(Some(path.to_path_buf()), name, &timeline);
+    Ok((timeline, document))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_file(path: &Path, timeline: &Timeline) -> Result<Document, DocumentError> {
+    let json = timeline.to_json()?;
+    crate::storage::write_atomically(path, &json)?;
+    let name = display_name(&path.file_name().unwrap_or_default().to_string_lossy());
+    Ok(Document::saved_as(Some(path.to_path_buf()), name, timeline))
+}
+
+/// Most recently opened or saved documents, newest first.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecentFiles {
+    paths: Vec<PathBuf>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RecentFiles {
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    pub fn push(&mut self, path: &Path) {
+        self.paths.retain(|p| p != path);
+        self.paths.insert(0, path.to_path_buf());
+        self.paths.truncate(MAX_RECENT_FILES);
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.paths.retain(|p| p != path);
+    }
+}
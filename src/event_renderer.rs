@@ -1,192 +1,978 @@
-use crate::timeline::{Event, Timeline};
+use crate::category::Category;
+use crate::date::{self, Date, DatePrecision};
+use crate::filter::{EventFilter, FilterDisplay};
+use crate::lane::LaneId;
+use crate::layout::{self, LanePlacement, LayoutMode, Orientation, Projection};
+use crate::minimap;
+use crate::time_axis;
+use crate::timeline::{Event, EventId, Timeline};
 use eframe::egui;
-use eframe::epaint::{Color32, Pos2};
+use eframe::epaint::{Color32, Pos2, Vec2};
+use std::ops::RangeInclusive;
 
-#[cfg(not(target_arch = "wasm32"))]
-use std::time::Duration;
-
-#[cfg(target_arch = "wasm32")]
-use web_time::Duration;
+/// How long a fly-to animation takes, in seconds.
+const FLIGHT_DURATION: f32 = 0.8;
+/// Room kept around a star when placing labels next to it.
+const STAR_RADIUS: f32 = 12.0;
 
 pub struct Camera {
     pub offset_x: f32,
     pub offset_y: f32,
     pub zoom: f32,
+    flight: Option<CameraFlight>,
+}
+
+/// An animated move of the camera, in un-zoomed layout coordinates.
+struct CameraFlight {
+    from_point: Pos2,
+    to_point: Pos2,
+    from_zoom: f32,
+    to_zoom: f32,
+    /// Where on screen the point being flown to ends up
+    screen_pos: Pos2,
+    start_time: f32,
+}
+
+impl Camera {
+    pub fn new() -> Self {
+        Self {
+            offset_x: 0.0,
+            offset_y: 0.0,
+            zoom: 1.0,
+            flight: None,
+        }
+    }
+
+    /// Pans (keeping the zoom) so that `point`, in un-zoomed layout coordinates, appears at
+    /// `screen_pos`.
+    pub fn center_on(&mut self, point: Pos2, screen_pos: Pos2) {
+        self.offset_x = screen_pos.x - point.x * self.zoom;
+        self.offset_y = screen_pos.y - point.y * self.zoom;
+    }
+
+    /// Zooms by `factor`, keeping whatever is under `anchor` (a screen position) in place.
+    pub fn zoom_at(&mut self, factor: f32, anchor: Pos2, limits: RangeInclusive<f32>) {
+        let point = Pos2::new(
+            (anchor.x - self.offset_x) / self.zoom,
+            (anchor.y - self.offset_y) / self.zoom,
+        );
+        self.zoom = (self.zoom * factor).clamp(*limits.start(), *limits.end());
+        self.center_on(point, anchor);
+    }
+
+    pub fn pan(&mut self, delta: Vec2) {
+        self.offset_x += delta.x;
+        self.offset_y += delta.y;
+    }
+
+    /// Starts a smooth pan and zoom that ends with `point` at `screen_pos` and the given zoom.
+    /// `time` is the animation clock also passed to [`Camera::update`].
+    pub fn fly_to(&mut self, point: Pos2, screen_pos: Pos2, zoom: f32, time: f32) {
+        // Whatever is at `screen_pos` right now is where the flight starts from
+        let from_point = Pos2::new(
+            (screen_pos.x - self.offset_x) / self.zoom,
+            (screen_pos.y - self.offset_y) / self.zoom,
+        );
+        self.flight = Some(CameraFlight {
+            from_point,
+            to_point: point,
+            from_zoom: self.zoom,
+            to_zoom: zoom,
+            screen_pos,
+            start_time: time,
+        });
+    }
+
+    /// Cancels a fly-to where it is, e.g. when the user takes over with the keyboard or mouse.
+    pub fn stop_flight(&mut self) {
+        self.flight = None;
+    }
+
+    /// Advances a fly-to animation; call once per frame before drawing.
+    pub fn update(&mut self, time: f32) {
+        let Some(flight) = &self.flight else {
+            return;
+        };
+
+        let progress = ((time - flight.start_time) / FLIGHT_DURATION).clamp(0.0, 1.0);
+        // Ease-out cubic, like frozen events resuming
+        let eased = 1.0 - (1.0 - progress).powi(3);
+
+        // Zoom geometrically so zooming in and out feel equally fast
+        self.zoom = flight.from_zoom * (flight.to_zoom / flight.from_zoom).powf(eased);
+        let point = flight.from_point.lerp(flight.to_point, eased);
+        let screen_pos = flight.screen_pos;
+        self.center_on(point, screen_pos);
+
+        if progress >= 1.0 {
+            self.flight = None;
+        }
+    }
+}
+
+/// Height of a collapsed lane, which shows only its header.
+const COLLAPSED_BAND_HEIGHT: f32 = 24.0;
+
+/// A horizontal track of the canvas: one per lane, plus one for events without a lane.
+pub struct Band {
+    /// `None` for the events without a lane (or whose lane was removed)
+    pub lane: Option<LaneId>,
+    pub name: String,
+    pub collapsed: bool,
+    pub top: f32,
+    pub height: f32,
+}
+
+impl Band {
+    pub fn center_y(&self) -> f32 {
+        self.top + self.height / 2.0
+    }
+}
+
+/// Where events sit before the camera and their animation move them: the full time range of
+/// the timeline spread across the panel, with a margin on either side, and the panel split
+/// into a band per lane. Everything is in layout space (see [`Projection`]).
+pub struct TimelineLayout {
+    /// The panel in layout space
+    pub rect: egui::Rect,
+    pub projection: Projection,
+    pub start_x: f32,
+    pub width: f32,
+    pub center_y: f32,
+    first_timestamp: i64,
+    /// Seconds from the first event to the end of the last one
+    pub time_range: i64,
+    /// Top to bottom; a single band covering the panel when the timeline has no lanes
+    pub bands: Vec<Band>,
+}
+
+impl TimelineLayout {
+    /// `None` for an empty timeline. `screen_rect` is the panel on screen.
+    pub fn new(
+        timeline: &Timeline,
+        screen_rect: egui::Rect,
+        orientation: Orientation,
+    ) -> Option<Self> {
+        let projection = Projection::new(orientation, screen_rect);
+        let rect = projection.layout_rect(screen_rect);
+        let events = timeline.events();
+        let mut first_timestamp = events.first()?.timestamp;
+        // Spans can end after the last event starts, so the range covers their ends too
+        let range_end = events
+            .iter()
+            .map(|e| e.last_timestamp())
+            .max()
+            .unwrap_or(first_timestamp);
+        let mut time_range = range_end - first_timestamp;
+        // A single moment still needs a scale, so the time axis can be drawn around it
+        if time_range == 0 {
+            time_range = 365 * date::SECONDS_PER_DAY;
+            first_timestamp -= time_range / 2;
+        }
+
+        Some(Self {
+            rect,
+            projection,
+            start_x: rect.left() + 50.0,
+            width: rect.width() - 100.0,
+            center_y: rect.center().y,
+            first_timestamp,
+            time_range,
+            bands: Self::bands(timeline, rect, orientation),
+        })
+    }
+
+    fn bands(timeline: &Timeline, rect: egui::Rect, orientation: Orientation) -> Vec<Band> {
+        let lanes = timeline.lanes();
+        if lanes.is_empty() {
+            return vec![Band {
+                lane: None,
+                name: String::new(),
+                collapsed: false,
+                top: rect.top(),
+                height: rect.height(),
+            }];
+        }
+
+        let mut bands: Vec<Band> = lanes
+            .iter()
+            .map(|lane| Band {
+                lane: Some(lane.id),
+                name: lane.name.clone(),
+                collapsed: lane.collapsed,
+                top: 0.0,
+                height: 0.0,
+            })
+            .collect();
+        let unassigned = timeline
+            .events()
+            .iter()
+            .any(|e| e.lane.and_then(|id| timeline.lane(id)).is_none());
+        if unassigned {
+            bands.push(Band {
+                lane: None,
+                name: "No lane".to_string(),
+                collapsed: false,
+                top: 0.0,
+                height: 0.0,
+            });
+        }
+
+        // Between the overview strip and the time axis, shared by the lanes that are open
+        let top = rect.top() + 40.0;
+        let bottom = rect.bottom() - time_axis::thickness(orientation) - 6.0;
+        let collapsed = bands.iter().filter(|b| b.collapsed).count();
+        let open = (bands.len() - collapsed).max(1);
+        let open_height =
+            ((bottom - top - collapsed as f32 * COLLAPSED_BAND_HEIGHT) / open as f32).max(0.0);
+        let mut y = top;
+        for band in &mut bands {
+            band.top = y;
+            band.height = if band.collapsed {
+                COLLAPSED_BAND_HEIGHT
+            } else {
+                open_height
+            };
+            y += band.height;
+        }
+        bands
+    }
+
+    /// Index into [`TimelineLayout::bands`] of the band the event is drawn in.
+    pub fn band_index(&self, event: &Event) -> usize {
+        self.bands
+            .iter()
+            .position(|b| b.lane.is_some() && b.lane == event.lane)
+            .or_else(|| self.bands.iter().position(|b| b.lane.is_none()))
+            .unwrap_or(0)
+    }
+
+    /// 0 at the first event, 1 at the end of the last.
+    pub fn ratio(&self, timestamp: i64) -> f32 {
+        ((timestamp - self.first_timestamp) as f64 / self.time_range as f64) as f32
+    }
+
+    pub fn x(&self, timestamp: i64) -> f32 {
+        self.start_x + self.ratio(timestamp) * self.width
+    }
+
+    /// Inverse of [`TimelineLayout::x`].
+    pub fn timestamp_at(&self, x: f32) -> i64 {
+        let ratio = (x - self.start_x) as f64 / self.width as f64;
+        self.first_timestamp
+            .saturating_add((ratio * self.time_range as f64) as i64)
+    }
+
+    /// Seconds of the timeline covered by one point of the view at the given zoom: the finest
+    /// detail that can be told apart.
+    pub fn seconds_per_point(&self, zoom: f32) -> f64 {
+        self.time_range as f64 / (self.width as f64 * zoom as f64).max(1.0)
+    }
+
+    /// Middle of the event (or of its span) on the resting line of its band.
+    pub fn event_center(&self, event: &Event) -> Pos2 {
+        let middle = event.timestamp + (event.last_timestamp() - event.timestamp) / 2;
+        let band = &self.bands[self.band_index(event)];
+        Pos2::new(self.x(middle), band.center_y())
+    }
 }
 
 pub struct EventInteraction {
-    pub clicked_index: Option<usize>,
-    pub delete_index: Option<usize>,
+    pub clicked_id: Option<EventId>,
+    pub delete_id: Option<EventId>,
+    /// Event that was double-clicked to open it in the edit form
+    pub edit_id: Option<EventId>,
+    /// From clicking a cluster: the point (before the camera transform) to centre on, and
+    /// how much to multiply the zoom by for its events to separate
+    pub zoom_to: Option<(Pos2, f32)>,
+    /// Lane whose header was clicked to collapse or expand it
+    pub toggle_lane: Option<LaneId>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn render_timeline_events(
     timeline: &Timeline,
     time: f32,
     ui: &mut egui::Ui,
     camera: &Camera,
-    frozen_positions: &mut std::collections::HashMap<usize, (f32, f32)>,
-    resume_start_times: &mut std::collections::HashMap<usize, f32>,
-    previous_clicked: Option<usize>,
+    frozen_positions: &mut std::collections::HashMap<EventId, (f32, f32)>,
+    resume_start_times: &mut std::collections::HashMap<EventId, f32>,
+    previous_clicked: Option<EventId>,
     image_cache: &std::collections::HashMap<String, egui::TextureHandle>,
+    filter: Option<&EventFilter>,
+    max_zoom: f32,
+    orientation: Orientation,
+    show_minimap: bool,
 ) -> EventInteraction {
-    if timeline.events().is_empty() {
+    let Some(layout) = TimelineLayout::new(timeline, ui.available_rect_before_wrap(), orientation)
+    else {
         return EventInteraction {
-            clicked_index: None,
-            delete_index: None,
+            clicked_id: None,
+            delete_id: None,
+            edit_id: None,
+            zoom_to: None,
+            toggle_lane: None,
         };
-    }
-
-    let rect = ui.available_rect_before_wrap();
+    };
+    // Positions below are in layout space, and only projected onto the screen to draw them
+    let rect = layout.rect;
+    let projection = layout.projection;
     let painter = ui.painter();
+    time_axis::render_time_axis(painter, &layout, camera);
+    render_zoom_limit_hint(painter, &layout, camera, max_zoom);
+    // Events that don't match the search are drawn through this one
+    let mut dimmed_painter = painter.clone();
+    dimmed_painter.multiply_opacity(0.15);
 
-    let timeline_y = rect.center().y;
-    let timeline_start_x = rect.left() + 50.0;
-    let timeline_end_x = rect.right() - 50.0;
-    let timeline_width = timeline_end_x - timeline_start_x;
+    let timeline_width = layout.width;
 
-    let pointer_pos = ui.input(|i| i.pointer.hover_pos());
+    // The overview strip, windows and a pinned cluster list are drawn over the events, and
+    // take the pointer while over them
+    let screen_pointer = ui
+        .input(|i| i.pointer.hover_pos())
+        .filter(|&p| !(show_minimap && minimap::hit_rect(&layout).contains(p)))
+        .filter(|&p| !is_over_area(ui, p));
+    let pointer_pos = screen_pointer.map(|p| projection.to_layout(p));
     let is_clicking = ui.input(|i| i.pointer.primary_down());
     let is_ctrl_held = ui.input(|i| i.modifiers.ctrl || i.modifiers.command);
-    let mut clicked_event_index = None;
+    // Deleting waits for the button to be released, so merely pressing it does nothing
+    let is_click_released = ui.input(|i| i.pointer.primary_clicked());
+    let is_double_click = ui.input(|i| {
+        i.pointer
+            .button_double_clicked(egui::PointerButton::Primary)
+    });
+    let mut clicked_event_id = None;
     let mut hovered_event_data: Option<(Pos2, Event, usize)> = None;
-    let mut delete_event_index = None;
+    let mut delete_event_id = None;
+    let mut edit_event_id = None;
 
     let events = timeline.events();
-    if let (Some(first), Some(last)) = (events.first(), events.last()) {
-        let time_range = last
-            .timestamp
-            .duration_since(first.timestamp)
-            .unwrap_or(Duration::from_secs(1));
-
-        for (i, event) in events.iter().enumerate() {
-            let time_offset = event
-                .timestamp
-                .duration_since(first.timestamp)
-                .unwrap_or(Duration::from_secs(0));
-
-            let position_ratio = if time_range.as_secs() > 0 {
-                time_offset.as_secs_f32() / time_range.as_secs_f32()
-            } else {
-                0.5
-            };
+    let time_range = layout.time_range;
+    let matches: Vec<bool> = events
+        .iter()
+        .map(|e| filter.is_none_or(|f| f.matches(timeline, e)))
+        .collect();
+    let band_of: Vec<usize> = events.iter().map(|e| layout.band_index(e)).collect();
+    // Filtered out, or in a collapsed lane
+    let hidden = |i: usize| {
+        (!matches[i] && filter.is_some_and(|f| f.display == FilterDisplay::Hide))
+            || layout.bands[band_of[i]].collapsed
+    };
+    // Screen length of a span event, which moves with its start
+    let span_length = |event: &Event| {
+        event.end_timestamp.map_or(0.0, |end| {
+            (layout.ratio(end) - layout.ratio(event.timestamp)) * timeline_width * camera.zoom
+        })
+    };
+    // Resting line of each band on screen
+    let band_axis_y = |band: usize| layout.bands[band].center_y() * camera.zoom + camera.offset_y;
+    let screen_x = |event: &Event| layout.x(event.timestamp) * camera.zoom + camera.offset_x;
 
-            // Base position on timeline
-            let base_x = timeline_start_x + position_ratio * timeline_width;
-
-            // Calculate animated position
-            let wave_speed = 1.5 + (i as f32 * 0.1) % 1.0;
-            let wave_phase = i as f32 * 2.0;
-            let wave_amplitude = 50.0 + (i as f32 * 10.0) % 30.0;
-            let wave_offset = (time * wave_speed + wave_phase).sin() * wave_amplitude;
-
-            let pulse_speed = 0.8 + (i as f32 * 0.15) % 0.5;
-            let pulse_phase = i as f32 * 1.5;
-            let pulse_amplitude = 30.0 + (i as f32 * 8.0) % 25.0;
-            let pulse_offset = (time * pulse_speed + pulse_phase).sin() * pulse_amplitude;
-
-            let animated_x = (base_x * camera.zoom) + camera.offset_x;
-            let animated_y =
-                ((timeline_y + wave_offset + pulse_offset) * camera.zoom) + camera.offset_y;
-
-            // Determine actual position to use (frozen or animating)
-            let (x, y) = if let Some(&frozen) = frozen_positions.get(&i) {
-                // Event is frozen - check if we're resuming
-                if let Some(&resume_start) = resume_start_times.get(&i) {
-                    // Calculate smooth resume animation with easing
-                    let resume_duration = 1.0; // 1 second smooth resume
-                    let resume_progress = ((time - resume_start) / resume_duration).min(1.0);
-
-                    // Ease-out cubic for smooth deceleration
-                    let eased_progress = 1.0 - (1.0 - resume_progress).powi(3);
-
-                    // Interpolate from frozen to animated position
-                    let lerp_x = frozen.0 + (animated_x - frozen.0) * eased_progress;
-                    let lerp_y = frozen.1 + (animated_y - frozen.1) * eased_progress;
-
-                    // If resume is complete, remove from frozen and resume tracking
-                    if resume_progress >= 1.0 {
-                        frozen_positions.remove(&i);
-                        resume_start_times.remove(&i);
-                        (animated_x, animated_y)
-                    } else {
-                        (lerp_x, lerp_y)
-                    }
-                } else {
-                    // Frozen without resuming
-                    frozen
+    // Even a single lane gets its header, so it can be told apart and collapsed
+    let (toggle_lane, over_header) = if !timeline.lanes().is_empty() {
+        render_band_headers(
+            painter,
+            &layout,
+            camera,
+            &band_of,
+            screen_pointer,
+            is_click_released,
+        )
+    } else {
+        (None, false)
+    };
+    // Headers are drawn over their band's events, and take the pointer while over them
+    let pointer_pos = pointer_pos.filter(|_| !over_header);
+
+    // Events that would land on the same pixels are merged into clusters. Long spans stay
+    // apart, since their bars are visibly distinct.
+    let mut clusters: Vec<(usize, Vec<usize>)> = Vec::new();
+    let mut clustered = vec![false; events.len()];
+    // At the zoom limit, clusters could never be opened up
+    let merge_clusters = camera.zoom < max_zoom;
+    for band in (0..layout.bands.len()).filter(|_| merge_clusters) {
+        let candidates: Vec<usize> = (0..events.len())
+            .filter(|&i| band_of[i] == band && !hidden(i))
+            .filter(|&i| span_length(&events[i]) < layout::CLUSTER_DISTANCE)
+            .collect();
+        let xs: Vec<f32> = candidates.iter().map(|&i| screen_x(&events[i])).collect();
+        for group in layout::group_nearby(&xs) {
+            if group.len() > 1 {
+                let members: Vec<usize> = group.iter().map(|&g| candidates[g]).collect();
+                for &i in &members {
+                    clustered[i] = true;
                 }
-            } else {
-                // Not frozen, use animated position
-                (animated_x, animated_y)
-            };
+                clusters.push((band, members));
+            }
+        }
+    }
 
-            let event_pos = Pos2::new(x, y);
+    // Stable lanes are assigned all at once, since where a label fits depends on its
+    // neighbours
+    let mut placements: Vec<Option<LanePlacement>> = vec![None; events.len()];
+    for band in (0..layout.bands.len()).filter(|_| timeline.layout() == LayoutMode::Stable) {
+        let visible: Vec<usize> = (0..events.len())
+            .filter(|&i| band_of[i] == band && !hidden(i) && !clustered[i])
+            .collect();
+        let extents: Vec<(f32, f32)> = visible
+            .iter()
+            .map(|&i| {
+                let event = &events[i];
+                let x = screen_x(event);
+                let length = span_length(event);
+                let label_size = painter
+                    .layout_no_wrap(
+                        event.title.clone(),
+                        egui::FontId::proportional(14.0),
+                        Color32::WHITE,
+                    )
+                    .size();
+                // Across the label, if it runs along the axis, or along it when it sits
+                // beside the star
+                let label_width = projection.vec_to_layout(label_size).x;
+                let middle = x + length / 2.0;
+                (
+                    (x - STAR_RADIUS).min(middle - label_width / 2.0),
+                    (x + length + STAR_RADIUS).max(middle + label_width / 2.0),
+                )
+            })
+            .collect();
+        // Leave room for the time axis (or the neighbouring bands)
+        let band_height = layout.bands[band].height * camera.zoom;
+        let lanes_per_side = ((band_height / 2.0 - 40.0) / orientation.lane_spacing()) as usize;
+        for (&i, placement) in visible
+            .iter()
+            .zip(layout::assign_lanes(&extents, lanes_per_side))
+        {
+            placements[i] = Some(placement);
+        }
+    }
+    let mut labels: Vec<LabelRequest> = Vec::new();
+    let hover_radius = match timeline.layout() {
+        LayoutMode::Cosmic => 50.0,
+        LayoutMode::Stable => orientation.lane_spacing() / 2.0,
+    };
 
-            // Check if mouse is hovering over this position
-            let is_hovered = if let Some(pointer) = pointer_pos {
-                let distance =
-                    ((pointer.x - event_pos.x).powi(2) + (pointer.y - event_pos.y).powi(2)).sqrt();
-                distance < 50.0
-            } else {
-                false
-            };
+    // Where each visible event is drawn this frame, in layout space
+    let mut positions: Vec<Option<Pos2>> = vec![None; events.len()];
+    for (i, event) in events.iter().enumerate() {
+        let id = event.id;
+        if hidden(i) || clustered[i] {
+            continue;
+        }
 
-            // Handle click-to-stop and release-to-resume
-            if is_hovered && is_clicking {
-                // Ctrl+Click to delete
-                if is_ctrl_held {
-                    delete_event_index = Some(i);
+        // Base position on timeline
+        let base_x = layout.x(event.timestamp);
+        let animated_x = (base_x * camera.zoom) + camera.offset_x;
+        let band = &layout.bands[band_of[i]];
+        let axis_y = band_axis_y(band_of[i]);
+        let animated_y = match placements[i] {
+            Some(placement) => axis_y + placement.lane as f32 * orientation.lane_spacing(),
+            None => {
+                // Waves shrink with the band, so they stay inside it
+                let wave_scale = (band.height / rect.height()).min(1.0);
+                // Drift on two overlapping waves
+                let wave_speed = 1.5 + (i as f32 * 0.1) % 1.0;
+                let wave_phase = i as f32 * 2.0;
+                let wave_amplitude = 50.0 + (i as f32 * 10.0) % 30.0;
+                let wave_offset = (time * wave_speed + wave_phase).sin() * wave_amplitude;
+
+                let pulse_speed = 0.8 + (i as f32 * 0.15) % 0.5;
+                let pulse_phase = i as f32 * 1.5;
+                let pulse_amplitude = 30.0 + (i as f32 * 8.0) % 25.0;
+                let pulse_offset = (time * pulse_speed + pulse_phase).sin() * pulse_amplitude;
+
+                ((band.center_y() + (wave_offset + pulse_offset) * wave_scale) * camera.zoom)
+                    + camera.offset_y
+            }
+        };
+
+        // Determine actual position to use (frozen or animating)
+        let (x, y) = if let Some(&frozen) = frozen_positions.get(&id) {
+            // Event is frozen - check if we're resuming
+            if let Some(&resume_start) = resume_start_times.get(&id) {
+                // Calculate smooth resume animation with easing
+                let resume_duration = 1.0; // 1 second smooth resume
+                let resume_progress = ((time - resume_start) / resume_duration).min(1.0);
+
+                // Ease-out cubic for smooth deceleration
+                let eased_progress = 1.0 - (1.0 - resume_progress).powi(3);
+
+                // Interpolate from frozen to animated position
+                let lerp_x = frozen.0 + (animated_x - frozen.0) * eased_progress;
+                let lerp_y = frozen.1 + (animated_y - frozen.1) * eased_progress;
+
+                // If resume is complete, remove from frozen and resume tracking
+                if resume_progress >= 1.0 {
+                    frozen_positions.remove(&id);
+                    resume_start_times.remove(&id);
+                    (animated_x, animated_y)
                 } else {
-                    // Regular click - freeze it
-                    if !frozen_positions.contains_key(&i) {
-                        frozen_positions.insert(i, (x, y));
-                        resume_start_times.remove(&i); // Cancel any ongoing resume
-                    }
-                    clicked_event_index = Some(i);
-                    hovered_event_data = Some((pointer_pos.unwrap(), event.clone(), i));
+                    (lerp_x, lerp_y)
                 }
-            } else if Some(i) == previous_clicked && !is_clicking {
-                // Was clicked last frame but released now - start smooth resume
-                if frozen_positions.contains_key(&i) && !resume_start_times.contains_key(&i) {
-                    resume_start_times.insert(i, time);
-                }
-            } else if Some(i) != previous_clicked && !is_clicking {
-                // Not clicked and wasn't clicked last frame - ensure clean state
-                if !resume_start_times.contains_key(&i) {
-                    frozen_positions.remove(&i);
+            } else {
+                // Frozen without resuming
+                frozen
+            }
+        } else {
+            // Not frozen, use animated position
+            (animated_x, animated_y)
+        };
+
+        positions[i] = Some(Pos2::new(x, y));
+    }
+
+    // Only the event nearest the pointer (anywhere along a span) reacts to it, so a click
+    // among overlapping stars never freezes, edits or deletes one that isn't under it
+    let hovered_index = pointer_pos.and_then(|pointer| {
+        positions
+            .iter()
+            .enumerate()
+            .filter_map(|(i, pos)| {
+                let pos = (*pos)?;
+                let end = Pos2::new(pos.x + span_length(&events[i]), pos.y);
+                let distance = distance_to_segment(pointer, pos, end);
+                (distance < hover_radius).then_some((i, distance))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    });
+
+    for (i, event) in events.iter().enumerate() {
+        let Some(event_pos) = positions[i] else {
+            continue;
+        };
+        let id = event.id;
+        let painter = if matches[i] { painter } else { &dimmed_painter };
+        let (x, y) = (event_pos.x, event_pos.y);
+        let span_length = span_length(event);
+        let span_end_pos = Pos2::new(x + span_length, y);
+        let axis_y = band_axis_y(band_of[i]);
+        let is_hovered = hovered_index == Some(i);
+
+        // Handle click-to-stop and release-to-resume
+        if is_hovered && is_clicking {
+            // Ctrl+Click deletes (below, on release) instead of freezing
+            if !is_ctrl_held {
+                // Regular click - freeze it
+                if let std::collections::hash_map::Entry::Vacant(entry) = frozen_positions.entry(id)
+                {
+                    entry.insert((x, y));
+                    resume_start_times.remove(&id); // Cancel any ongoing resume
                 }
+                clicked_event_id = Some(id);
+                hovered_event_data = Some((screen_pointer.unwrap(), event.clone(), i));
+            }
+        } else if Some(id) == previous_clicked && !is_clicking {
+            // Was clicked last frame but released now - start smooth resume
+            if frozen_positions.contains_key(&id) && !resume_start_times.contains_key(&id) {
+                resume_start_times.insert(id, time);
             }
+        } else if Some(id) != previous_clicked && !is_clicking {
+            // Not clicked and wasn't clicked last frame - ensure clean state
+            if !resume_start_times.contains_key(&id) {
+                frozen_positions.remove(&id);
+            }
+        }
+
+        if is_hovered && is_click_released && is_ctrl_held {
+            delete_event_id = Some(id);
+        }
+
+        if is_hovered && is_double_click && !is_ctrl_held {
+            edit_event_id = Some(id);
+        }
+
+        // Show tooltip on hover
+        if is_hovered {
+            hovered_event_data = Some((screen_pointer.unwrap(), event.clone(), i));
+        }
+
+        // Nothing to draw for events well outside the view
+        let bounds = egui::Rect::from_two_pos(event_pos, span_end_pos).expand(100.0);
+        if !rect.intersects(bounds) {
+            continue;
+        }
+
+        let tint = timeline.event_color(event);
+        let event_pos = projection.to_screen(event_pos);
+        let span_end_pos = projection.to_screen(span_end_pos);
+        let show_label = placements[i].is_none_or(|p| p.show_label) || is_hovered;
+        if show_label {
+            labels.push(LabelRequest {
+                anchor: projection.to_screen(Pos2::new(x + span_length / 2.0, y)),
+                title: &event.title,
+                tint,
+                index: i,
+                hovered: is_hovered,
+                dimmed: !matches[i],
+                priority: (
+                    is_hovered,
+                    matches[i],
+                    event.is_span(),
+                    representative_rank(i),
+                ),
+            });
+        }
+        if placements[i].is_some() {
+            // Stem down to the axis, so the date can be read off it
+            painter.line_segment(
+                [projection.to_screen(Pos2::new(x, axis_y)), event_pos],
+                egui::Stroke::new(1.0, with_alpha(tint, 50)),
+            );
+        }
+        if event.is_span() {
+            render_span_bar(painter, event_pos, span_end_pos, tint, i, time, is_hovered);
+        } else {
+            if event.is_uncertain() {
+                let spread = (event.uncertainty_seconds() as f64 / time_range.max(1) as f64) as f32
+                    * timeline_width
+                    * camera.zoom;
+                render_uncertainty_haze(
+                    painter,
+                    event_pos,
+                    projection.time_direction() * spread.clamp(15.0, 300.0),
+                    tint,
+                    time,
+                    i as f32,
+                );
+            }
+            render_burning_star(painter, event_pos, tint, i, time, event, is_hovered);
+        }
+    }
+
+    // A cluster whose members can't be told apart even at full zoom (such as events on the
+    // same day) has its list pinned open on click instead
+    let pinned_id = egui::Id::new("pinned_cluster");
+    let mut pinned: Option<(Vec<EventId>, Pos2)> = ui.data(|d| d.get_temp(pinned_id));
+    if is_click_released && screen_pointer.is_some() {
+        pinned = None;
+    }
+
+    // Clusters go on the axis, which stable lanes leave free
+    let mut hovered_cluster = None;
+    let mut zoom_to = None;
+    for (n, (band, members)) in clusters.iter().enumerate() {
+        let xs: Vec<f32> = members.iter().map(|&i| screen_x(&events[i])).collect();
+        let (left, right) = (xs[0], xs[xs.len() - 1]);
+        let pos = Pos2::new((left + right) / 2.0, band_axis_y(*band));
+        let radius = 14.0 + 6.0 * (members.len() as f32).ln();
+        if !rect.expand(radius * 3.0).contains(pos) {
+            continue;
+        }
 
-            // Show tooltip on hover
-            if is_hovered {
-                hovered_event_data = Some((pointer_pos.unwrap(), event.clone(), i));
+        let is_hovered = pointer_pos.is_some_and(|p| p.distance(pos) < radius + 10.0);
+        let separable = (right - left) / camera.zoom * max_zoom > layout::CLUSTER_DISTANCE;
+        if is_hovered {
+            hovered_cluster = Some((screen_pointer.unwrap(), members, separable));
+            if is_click_released && !is_ctrl_held && !separable {
+                let ids = members.iter().map(|&i| events[i].id).collect();
+                pinned = Some((ids, screen_pointer.unwrap()));
+            } else if is_click_released && !is_ctrl_held {
+                // Enough to spread the members over a few cluster widths
+                let factor = (3.0 * layout::CLUSTER_DISTANCE / (right - left).max(1.0)).max(2.0);
+                let point = Pos2::new(
+                    (pos.x - camera.offset_x) / camera.zoom,
+                    (pos.y - camera.offset_y) / camera.zoom,
+                );
+                zoom_to = Some((point, factor));
             }
+        }
+
+        let tints: Vec<Color32> = members
+            .iter()
+            .map(|&i| timeline.event_color(&events[i]))
+            .collect();
+        let painter = if members.iter().any(|&i| matches[i]) {
+            painter
+        } else {
+            &dimmed_painter
+        };
+        render_cluster(
+            painter,
+            projection.to_screen(pos),
+            radius,
+            blend(&tints),
+            members.len(),
+            n,
+            time,
+            is_hovered,
+        );
+    }
+
+    place_labels(painter, &dimmed_painter, labels, orientation, time);
 
-            render_burning_star(painter, event_pos, i, time, event, is_hovered);
-            render_event_label(painter, x, y, event, time, i, is_hovered);
+    if let Some((ids, pos)) = &pinned {
+        let members: Vec<&Event> = ids.iter().filter_map(|&id| timeline.event(id)).collect();
+        if !render_pinned_cluster(ui, *pos, &members, timeline) {
+            pinned = None;
         }
     }
+    ui.data_mut(|d| match pinned {
+        Some(pinned) => d.insert_temp(pinned_id, pinned),
+        None => d.remove::<(Vec<EventId>, Pos2)>(pinned_id),
+    });
 
     // Render tooltip after releasing painter borrow
-    if let Some((pos, event, _index)) = hovered_event_data {
-        render_event_tooltip(ui, pos, &event, image_cache);
+    if let Some((pos, members, separable)) = hovered_cluster {
+        let members: Vec<&Event> = members.iter().map(|&i| &events[i]).collect();
+        render_cluster_tooltip(ui, pos, &members, timeline, separable);
+    } else if let Some((pos, event, _index)) = hovered_event_data {
+        let category = event.category.and_then(|id| timeline.category(id));
+        let tint = timeline.event_color(&event);
+        render_event_tooltip(ui, pos, &event, category, tint, image_cache);
     }
 
     EventInteraction {
-        clicked_index: clicked_event_index,
-        delete_index: delete_event_index,
+        clicked_id: clicked_event_id,
+        delete_id: delete_event_id,
+        edit_id: edit_event_id,
+        zoom_to,
+        toggle_lane,
+    }
+}
+
+/// At the zoom limit of a timeline so long that single years still can't be told apart
+/// (see [`crate::settings::ZOOM_LIMIT_RANGE`]), says so just above the time axis.
+fn render_zoom_limit_hint(
+    painter: &egui::Painter,
+    layout: &TimelineLayout,
+    camera: &Camera,
+    max_zoom: f32,
+) {
+    let finest = layout.seconds_per_point(max_zoom);
+    if camera.zoom < max_zoom || finest < YEAR_SECONDS {
+        return;
+    }
+
+    let rect = layout.rect;
+    let pos = Pos2::new(
+        rect.center().x,
+        rect.bottom() - time_axis::thickness(layout.projection.orientation) - 8.0,
+    );
+    let align = match layout.projection.orientation {
+        Orientation::Horizontal => egui::Align2::CENTER_BOTTOM,
+        Orientation::Vertical => egui::Align2::RIGHT_CENTER,
+    };
+    painter.text(
+        layout.projection.to_screen(pos),
+        align,
+        format!(
+            "Zoom limit: {} per point. Timelines this long can't zoom in to single years.",
+            format_duration(finest)
+        ),
+        egui::FontId::proportional(12.0),
+        Color32::from_gray(150),
+    );
+}
+
+const YEAR_SECONDS: f64 = 365.25 * date::SECONDS_PER_DAY as f64;
+
+/// A rough length of time, to two significant figures: "about 1,400 years".
+pub fn format_duration(seconds: f64) -> String {
+    let units = [
+        (YEAR_SECONDS, "year"),
+        (date::SECONDS_PER_DAY as f64, "day"),
+        (3600.0, "hour"),
+        (60.0, "minute"),
+    ];
+    let Some((count, unit)) = units
+        .into_iter()
+        .find(|&(length, _)| seconds >= length)
+        .map(|(length, unit)| (seconds / length, unit))
+    else {
+        return "under a minute".to_string();
+    };
+
+    let magnitude = 10f64.powi((count.log10().floor() as i32 - 1).max(0));
+    let count = ((count / magnitude).round() * magnitude) as u64;
+    let digits = count.to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    let plural = if count == 1 { "" } else { "s" };
+    format!("about {} {}{}", grouped, unit, plural)
+}
+
+/// Whether `pos` is over a window or popup drawn on top of the timeline.
+fn is_over_area(ui: &egui::Ui, pos: Pos2) -> bool {
+    ui.ctx()
+        .layer_id_at(pos)
+        .is_some_and(|layer| layer != ui.layer_id())
+}
+
+/// Average of the colours, for a cluster of events in different categories.
+fn blend(colors: &[Color32]) -> Color32 {
+    let n = colors.len().max(1) as u32;
+    let sum = |channel: fn(&Color32) -> u8| {
+        (colors.iter().map(|c| channel(c) as u32).sum::<u32>() / n) as u8
+    };
+    Color32::from_rgb(sum(Color32::r), sum(Color32::g), sum(Color32::b))
+}
+
+fn with_alpha(color: Color32, alpha: u8) -> Color32 {
+    Color32::from_rgba_unmultiplied(color.r(), color.g(), color.b(), alpha)
+}
+
+/// Mixes `amount` (0..=1) of white into the colour, for the hotter inner parts of a star.
+fn lighten(color: Color32, amount: f32) -> Color32 {
+    let mix = |c: u8| (c as f32 + (255.0 - c as f32) * amount) as u8;
+    Color32::from_rgb(mix(color.r()), mix(color.g()), mix(color.b()))
+}
+
+fn darken(color: Color32, amount: f32) -> Color32 {
+    let mix = |c: u8| (c as f32 * (1.0 - amount)) as u8;
+    Color32::from_rgb(mix(color.r()), mix(color.g()), mix(color.b()))
+}
+
+fn distance_to_segment(point: Pos2, start: Pos2, end: Pos2) -> f32 {
+    let segment = end - start;
+    let length_sq = segment.length_sq();
+    if length_sq == 0.0 {
+        return point.distance(start);
+    }
+    let t = ((point - start).dot(segment) / length_sq).clamp(0.0, 1.0);
+    point.distance(start + segment * t)
+}
+
+/// Draws the lane bands' backgrounds and headers (which stick to the start of the view while
+/// their band is on screen). Returns the lane whose header was clicked, if any, and whether
+/// `pointer` (on screen) is over a header.
+fn render_band_headers(
+    painter: &egui::Painter,
+    layout: &TimelineLayout,
+    camera: &Camera,
+    band_of: &[usize],
+    pointer: Option<Pos2>,
+    clicked: bool,
+) -> (Option<LaneId>, bool) {
+    let (rect, projection) = (layout.rect, layout.projection);
+    let mut toggled = None;
+    let mut over_header = false;
+    for (index, band) in layout.bands.iter().enumerate() {
+        let top = band.top * camera.zoom + camera.offset_y;
+        let bottom = top + band.height * camera.zoom;
+        if bottom < rect.top() || top > rect.bottom() {
+            continue;
+        }
+
+        let band_rect = egui::Rect::from_x_y_ranges(rect.x_range(), top..=bottom);
+        if index % 2 == 1 {
+            painter.rect_filled(
+                projection.rect_to_screen(band_rect),
+                0.0,
+                Color32::from_white_alpha(6),
+            );
+        }
+        painter.line_segment(
+            [
+                projection.to_screen(Pos2::new(rect.left(), top)),
+                projection.to_screen(Pos2::new(rect.right(), top)),
+            ],
+            egui::Stroke::new(1.0, Color32::from_white_alpha(30)),
+        );
+
+        let count = band_of.iter().filter(|&&b| b == index).count();
+        let arrow = match (band.lane, band.collapsed) {
+            (None, _) => "",
+            (Some(_), true) => "⏵ ",
+            (Some(_), false) => "⏷ ",
+        };
+        // Below the overview strip, but never outside its own band
+        let header_y = top.max(rect.top() + 36.0).min(bottom - 20.0) + 4.0;
+        let header = painter.text(
+            projection.to_screen(Pos2::new(rect.left() + 8.0, header_y)),
+            egui::Align2::LEFT_TOP,
+            format!("{}{} ({})", arrow, band.name, count),
+            egui::FontId::proportional(13.0),
+            Color32::from_gray(if band.collapsed { 130 } else { 190 }),
+        );
+        if pointer.is_some_and(|p| header.expand(4.0).contains(p)) {
+            over_header = true;
+            if clicked {
+                toggled = band.lane;
+            }
+        }
+    }
+    (toggled, over_header)
+}
+
+fn render_span_bar(
+    painter: &egui::Painter,
+    start: Pos2,
+    end: Pos2,
+    tint: Color32,
+    index: usize,
+    time: f32,
+    is_hovered: bool,
+) {
+    let i = index as f32;
+    let slow_pulse = ((time * 0.6 + i).sin() + 1.0) / 2.0;
+    let fast_flicker = ((time * 4.5 + i * 3.0).sin() + 1.0) / 2.0;
+    let intensity = (0.6 + slow_pulse * 0.3 + fast_flicker * 0.1).clamp(0.4, 1.0);
+    let thickness = if is_hovered { 8.0 } else { 5.0 };
+
+    // Glow layers, widest and faintest first
+    let layers = [(5.0, 25.0), (3.2, 45.0), (2.0, 80.0), (1.2, 140.0)];
+    for (width_scale, alpha) in layers {
+        painter.line_segment(
+            [start, end],
+            egui::Stroke::new(
+                thickness * width_scale,
+                with_alpha(tint, (alpha * intensity) as u8),
+            ),
+        );
+    }
+
+    // White hot core line
+    painter.line_segment(
+        [start, end],
+        egui::Stroke::new(
+            thickness * 0.4,
+            with_alpha(lighten(tint, 0.8), (220.0 * intensity) as u8),
+        ),
+    );
+
+    // Sparks drifting along the bar like a comet trail
+    let length = (end - start).length();
+    let spark_count = ((length / 40.0) as usize).clamp(2, 24);
+    for spark in 0..spark_count {
+        let phase = (time * 0.15 + spark as f32 / spark_count as f32 + i * 0.37).fract();
+        let spark_pos = start + (end - start) * phase;
+        let spark_alpha = ((1.0 - (phase - 0.5).abs() * 2.0) * 200.0 * intensity) as u8;
+        painter.circle_filled(
+            spark_pos,
+            thickness * 0.5,
+            with_alpha(lighten(tint, 0.9), spark_alpha),
+        );
+    }
+
+    // Bright caps marking the start and end dates
+    for cap in [start, end] {
+        painter.circle_filled(
+            cap,
+            thickness * 2.0,
+            with_alpha(tint, (90.0 * intensity) as u8),
+        );
+        painter.circle_filled(cap, thickness * 0.9, lighten(tint, 0.7));
     }
 }
 
 fn render_burning_star(
     painter: &egui::Painter,
     event_pos: Pos2,
+    tint: Color32,
     index: usize,
     time: f32,
-    _event: &Event,
+    event: &Event,
     is_hovered: bool,
 ) {
     let i = index as f32;
+    let uncertain = event.is_uncertain();
 
     // Multiple frequency oscillations for realistic flame-like behavior
     let slow_pulse = ((time * 0.6 + i).sin() + 1.0) / 2.0;
@@ -203,37 +989,111 @@ fn render_burning_star(
     let base_size = if is_hovered { 20.0 } else { 14.0 };
     let star_size = base_size * size_variation;
 
+    // Uncertain dates get a soft, ray-less glow with no sharp core
+    if uncertain {
+        render_glow_layers(painter, event_pos, star_size * 1.3, tint, intensity * 0.7);
+        return;
+    }
+
     // Draw star rays/flares
-    render_star_rays(
-        painter,
-        event_pos,
-        star_size,
-        fast_flicker,
+    let rays = StarRays {
+        size: star_size,
+        flicker: fast_flicker,
         intensity,
-        time,
-        i,
-    );
+        rotation: time * 0.3 + i,
+    };
+    render_star_rays(painter, event_pos, tint, &rays);
 
     // Draw glow layers
-    render_glow_layers(painter, event_pos, star_size, intensity);
+    render_glow_layers(painter, event_pos, star_size, tint, intensity);
 
     // Draw core
     render_star_core(painter, event_pos, star_size, intensity, rapid_twinkle);
 }
 
-fn render_star_rays(
+/// A larger star standing in for several events, with a badge showing how many.
+#[allow(clippy::too_many_arguments)]
+fn render_cluster(
+    painter: &egui::Painter,
+    pos: Pos2,
+    radius: f32,
+    tint: Color32,
+    count: usize,
+    index: usize,
+    time: f32,
+    is_hovered: bool,
+) {
+    let i = index as f32;
+    let slow_pulse = ((time * 0.5 + i).sin() + 1.0) / 2.0;
+    let rapid_twinkle = ((time * 6.0 + i * 5.0).cos() + 1.0) / 2.0;
+    let intensity = (0.7 + slow_pulse * 0.3).clamp(0.4, 1.0);
+    let size = if is_hovered { radius * 1.2 } else { radius };
+
+    let rays = StarRays {
+        size,
+        flicker: rapid_twinkle,
+        intensity,
+        rotation: time * 0.15 + i,
+    };
+    render_star_rays(painter, pos, tint, &rays);
+    render_glow_layers(painter, pos, size, tint, intensity);
+    render_star_core(painter, pos, size, intensity, rapid_twinkle);
+
+    let badge_pos = pos + Vec2::new(size * 0.8, -size * 0.8);
+    let text = count.to_string();
+    let badge_radius = 7.0 + 3.0 * text.len() as f32;
+    painter.circle(
+        badge_pos,
+        badge_radius,
+        Color32::from_rgba_unmultiplied(20, 20, 20, 230),
+        egui::Stroke::new(1.5, tint),
+    );
+    painter.text(
+        badge_pos,
+        egui::Align2::CENTER_CENTER,
+        text,
+        egui::FontId::proportional(11.0),
+        Color32::WHITE,
+    );
+}
+
+/// `spread` runs along the time axis, from the star to the edge of its uncertainty window.
+fn render_uncertainty_haze(
     painter: &egui::Painter,
     event_pos: Pos2,
-    star_size: f32,
-    fast_flicker: f32,
-    intensity: f32,
+    spread: Vec2,
+    tint: Color32,
     time: f32,
     i: f32,
 ) {
-    let ray_rotation = time * 0.3 + i;
+    let shimmer = ((time * 0.9 + i).sin() + 1.0) / 2.0;
+    let spread_length = spread.length();
+
+    // Overlapping soft puffs along the time axis, fading towards the edges of the window
+    let puffs = 9;
+    for puff in 0..puffs {
+        let t = puff as f32 / (puffs - 1) as f32 * 2.0 - 1.0;
+        let falloff = (-t * t * 2.5).exp();
+        let puff_pos = event_pos + t * spread;
+        let puff_size = 10.0 + spread_length * 0.25 * falloff;
+        let alpha = (falloff * (18.0 + shimmer * 10.0)) as u8;
+        painter.circle_filled(puff_pos, puff_size, with_alpha(lighten(tint, 0.2), alpha));
+    }
+}
+
+/// Shape of a star's four flares at the current frame.
+struct StarRays {
+    size: f32,
+    flicker: f32,
+    intensity: f32,
+    rotation: f32,
+}
+
+fn render_star_rays(painter: &egui::Painter, event_pos: Pos2, tint: Color32, rays: &StarRays) {
+    let ray_color = lighten(tint, 0.1);
     for ray_i in 0..4 {
-        let angle = ray_rotation + (ray_i as f32 * std::f32::consts::PI / 2.0);
-        let ray_length = star_size * (2.5 + fast_flicker * 1.5);
+        let angle = rays.rotation + (ray_i as f32 * std::f32::consts::PI / 2.0);
+        let ray_length = rays.size * (2.5 + rays.flicker * 1.5);
 
         for t in 0..8 {
             let t_norm = t as f32 / 8.0;
@@ -241,35 +1101,33 @@ fn render_star_rays(
                 event_pos.x + angle.cos() * ray_length * t_norm,
                 event_pos.y + angle.sin() * ray_length * t_norm,
             );
-            let ray_alpha = ((1.0 - t_norm) * intensity * 40.0) as u8;
+            let ray_alpha = ((1.0 - t_norm) * rays.intensity * 40.0) as u8;
             let ray_size = (1.0 - t_norm * 0.8) * 2.0;
-            painter.circle_filled(
-                ray_pos,
-                ray_size,
-                Color32::from_rgba_unmultiplied(255, 215, 0, ray_alpha),
-            );
+            painter.circle_filled(ray_pos, ray_size, with_alpha(ray_color, ray_alpha));
         }
     }
 }
 
-fn render_glow_layers(painter: &egui::Painter, event_pos: Pos2, star_size: f32, intensity: f32) {
+fn render_glow_layers(
+    painter: &egui::Painter,
+    event_pos: Pos2,
+    star_size: f32,
+    tint: Color32,
+    intensity: f32,
+) {
     // Outer burning halo
     let halo_size = star_size * 4.0;
     let halo_alpha = (25.0 * intensity) as u8;
     painter.circle_filled(
         event_pos,
         halo_size,
-        Color32::from_rgba_unmultiplied(255, 180, 0, halo_alpha),
+        with_alpha(darken(tint, 0.1), halo_alpha),
     );
 
     // Far glow
     let far_glow_size = star_size * 3.0;
     let far_alpha = (40.0 * intensity) as u8;
-    painter.circle_filled(
-        event_pos,
-        far_glow_size,
-        Color32::from_rgba_unmultiplied(255, 200, 0, far_alpha),
-    );
+    painter.circle_filled(event_pos, far_glow_size, with_alpha(tint, far_alpha));
 
     // Outer glow
     let outer_glow_size = star_size * 2.2;
@@ -277,7 +1135,7 @@ fn render_glow_layers(painter: &egui::Painter, event_pos: Pos2, star_size: f32,
     painter.circle_filled(
         event_pos,
         outer_glow_size,
-        Color32::from_rgba_unmultiplied(255, 215, 0, outer_alpha),
+        with_alpha(lighten(tint, 0.1), outer_alpha),
     );
 
     // Middle glow
@@ -286,16 +1144,15 @@ fn render_glow_layers(painter: &egui::Painter, event_pos: Pos2, star_size: f32,
     painter.circle_filled(
         event_pos,
         mid_glow_size,
-        Color32::from_rgba_unmultiplied(255, 220, 50, mid_alpha),
+        with_alpha(lighten(tint, 0.2), mid_alpha),
     );
 
     // Inner bright core
     let inner_size = star_size * 0.8;
-    let inner_brightness = (200.0 + 55.0 * intensity) as u8;
     painter.circle_filled(
         event_pos,
         inner_size,
-        Color32::from_rgb(255, inner_brightness, 100),
+        lighten(tint, 0.35 + 0.25 * intensity),
     );
 }
 
@@ -320,11 +1177,129 @@ fn render_star_core(
     painter.circle_filled(event_pos, spark_size, Color32::WHITE);
 }
 
+/// A title waiting for [`place_labels`], which decides whether and where it goes.
+struct LabelRequest<'a> {
+    /// The star, or the middle of a span, on screen
+    anchor: Pos2,
+    title: &'a str,
+    tint: Color32,
+    index: usize,
+    hovered: bool,
+    /// Drawn faded, for events that don't match the search
+    dimmed: bool,
+    /// Compared highest first: hovered, then search matches, then spans, then
+    /// [`representative_rank`]
+    priority: (bool, bool, bool, u32),
+}
+
+/// Ranks events so that, taken highest first, they are spread evenly along the timeline:
+/// the first, then every 2^k-th for decreasing k. When zoomed out only the top few labels
+/// find room, so the ones shown are a fair sample rather than a clump at one end.
+fn representative_rank(index: usize) -> u32 {
+    if index == 0 {
+        u32::MAX
+    } else {
+        index.trailing_zeros()
+    }
+}
+
+/// Draws as many labels as fit without overlapping, most important first. A label that
+/// doesn't fit above its star (or, on a vertical timeline, to its right) is tried on the
+/// other side, then stacked further out with a leader line back to the star; one that fits
+/// nowhere is left out (the tooltip still shows it).
+fn place_labels(
+    painter: &egui::Painter,
+    dimmed_painter: &egui::Painter,
+    mut labels: Vec<LabelRequest>,
+    orientation: Orientation,
+    time: f32,
+) {
+    const GAP: f32 = 30.0;
+    const STACK_STEP: f32 = 20.0;
+
+    labels.sort_by_key(|label| std::cmp::Reverse(label.priority));
+    let mut taken: Vec<egui::Rect> = Vec::new();
+    for label in labels {
+        let font_size = if label.hovered { 16.0 } else { 14.0 };
+        let size = painter
+            .layout_no_wrap(
+                label.title.to_string(),
+                egui::FontId::proportional(font_size),
+                Color32::WHITE,
+            )
+            .size();
+        let (x, y) = (label.anchor.x - size.x / 2.0, label.anchor.y);
+        let spots = match orientation {
+            Orientation::Horizontal => {
+                let above = |stack: f32| {
+                    let top = y - GAP - size.y - stack * STACK_STEP;
+                    egui::Rect::from_min_size(Pos2::new(x, top), size)
+                };
+                let below = |stack: f32| {
+                    let top = y + GAP - 8.0 + stack * STACK_STEP;
+                    egui::Rect::from_min_size(Pos2::new(x, top), size)
+                };
+                [above(0.0), below(0.0), above(1.0), below(1.0), above(2.0)]
+            }
+            // Beside the star, stacking along the axis
+            Orientation::Vertical => {
+                let top = |stack: f32| y - size.y / 2.0 + stack * STACK_STEP;
+                let right = |stack: f32| {
+                    egui::Rect::from_min_size(Pos2::new(label.anchor.x + GAP, top(stack)), size)
+                };
+                let left = |stack: f32| {
+                    let left = label.anchor.x - GAP - size.x;
+                    egui::Rect::from_min_size(Pos2::new(left, top(stack)), size)
+                };
+                [right(0.0), left(0.0), right(1.0), left(1.0), right(-1.0)]
+            }
+        };
+
+        let free = spots
+            .iter()
+            .position(|spot| !taken.iter().any(|t| t.intersects(*spot)));
+        // The hovered label is placed first, so it always gets the usual spot
+        let Some(spot_index) = free else {
+            continue;
+        };
+        let spot = spots[spot_index];
+        taken.push(spot.expand(2.0));
+
+        let painter = if label.dimmed {
+            dimmed_painter
+        } else {
+            painter
+        };
+        if spot_index > 1 {
+            let end = match orientation {
+                Orientation::Horizontal if spot.center().y < y => spot.center_bottom(),
+                Orientation::Horizontal => spot.center_top(),
+                Orientation::Vertical if spot.center().x > label.anchor.x => spot.left_center(),
+                Orientation::Vertical => spot.right_center(),
+            };
+            painter.line_segment(
+                [label.anchor, end],
+                egui::Stroke::new(1.0, with_alpha(label.tint, 70)),
+            );
+        }
+        render_event_label(
+            painter,
+            spot.center_bottom(),
+            label.title,
+            label.tint,
+            time,
+            label.index,
+            label.hovered,
+        );
+    }
+}
+
+/// Draws a title with its bottom edge centred on `text_pos`.
 fn render_event_label(
     painter: &egui::Painter,
-    x: f32,
-    y: f32,
-    event: &Event,
+    text_pos: Pos2,
+    title: &str,
+    tint: Color32,
     time: f32,
     index: usize,
     is_hovered: bool,
@@ -339,21 +1314,13 @@ fn render_event_label(
         (slow_pulse * 0.4 + medium_pulse * 0.3 + fast_flicker * 0.2 + rapid_twinkle * 0.1)
             .clamp(0.4, 1.0);
 
-    // Position text above the event (which already has camera transform applied)
-    let text_pos = Pos2::new(x, y - 30.0);
-
     // Text glow
     painter.text(
         text_pos,
         egui::Align2::CENTER_BOTTOM,
-        &event.title,
+        title,
         egui::FontId::proportional(14.0),
-        Color32::from_rgba_unmultiplied(
-            event.color.r(),
-            event.color.g(),
-            event.color.b(),
-            (100.0 * intensity) as u8,
-        ),
+        with_alpha(tint, (100.0 * intensity) as u8),
     );
 
     // Text main
@@ -361,9 +1328,9 @@ fn render_event_label(
     painter.text(
         text_pos,
         egui::Align2::CENTER_BOTTOM,
-        &event.title,
+        title,
         egui::FontId::proportional(font_size),
-        Color32::WHITE,
+        lighten(tint, 0.75),
     );
 }
 
@@ -371,6 +1338,8 @@ fn render_event_tooltip(
     ui: &mut egui::Ui,
     pointer_pos: Pos2,
     event: &Event,
+    category: Option<&Category>,
+    tint: Color32,
     image_cache: &std::collections::HashMap<String, egui::TextureHandle>,
 ) {
     egui::Area::new(egui::Id::new("event_tooltip"))
@@ -379,7 +1348,7 @@ fn render_event_tooltip(
         .show(ui.ctx(), |ui| {
             egui::Frame::popup(ui.style())
                 .fill(Color32::from_rgba_unmultiplied(20, 20, 20, 240))
-                .stroke(egui::Stroke::new(2.0, Color32::from_rgb(255, 215, 0)))
+                .stroke(egui::Stroke::new(2.0, tint))
                 .corner_radius(8.0)
                 .inner_margin(12.0)
                 .show(ui, |ui| {
@@ -389,15 +1358,33 @@ fn render_event_tooltip(
                         ui.label(
                             egui::RichText::new(&event.title)
                                 .size(18.0)
-                                .color(Color32::from_rgb(255, 215, 0))
+                                .color(lighten(tint, 0.3))
                                 .strong(),
                         );
 
+                        if category.is_some() || !event.tags.is_empty() {
+                            ui.horizontal_wrapped(|ui| {
+                                if let Some(category) = category {
+                                    ui.label(
+                                        egui::RichText::new(format!("● {}", category.name))
+                                            .size(12.0)
+                                            .color(tint),
+                                    );
+                                }
+                                for tag in &event.tags {
+                                    ui.label(
+                                        egui::RichText::new(format!("#{}", tag))
+                                            .size(12.0)
+                                            .color(Color32::from_gray(170)),
+                                    );
+                                }
+                            });
+                        }
+
                         ui.add_space(4.0);
 
-                        // Display the full date
-                        let month_name = get_month_name(event.month);
-                        let date_str = format!("{} {}, {}", month_name, event.day, event.year);
+                        // Display the date as precisely as it is known
+                        let date_str = format_event_date(event);
                         ui.label(
                             egui::RichText::new(date_str)
                                 .size(13.0)
@@ -444,16 +1431,158 @@ fn render_event_tooltip(
                         ui.separator();
                         ui.add_space(4.0);
                         ui.label(
-                            egui::RichText::new("💡 Ctrl+Click to delete")
-                                .color(Color32::from_gray(180))
-                                .italics(),
+                            egui::RichText::new(
+                                "💡 Double-click to edit, Ctrl+Click to delete (Ctrl+Z to undo)",
+                            )
+                            .color(Color32::from_gray(180))
+                            .italics(),
                         );
                     });
                 });
         });
 }
 
-fn get_month_name(month: u8) -> &'static str {
+fn render_cluster_tooltip(
+    ui: &mut egui::Ui,
+    pointer_pos: Pos2,
+    members: &[&Event],
+    timeline: &Timeline,
+    separable: bool,
+) {
+    const MAX_LISTED: usize = 12;
+
+    egui::Area::new(egui::Id::new("cluster_tooltip"))
+        .fixed_pos(egui::Pos2::new(pointer_pos.x + 15.0, pointer_pos.y + 15.0))
+        .interactable(false)
+        .show(ui.ctx(), |ui| {
+            cluster_frame(ui).show(ui, |ui| {
+                ui.set_max_width(300.0);
+                let listed = &members[..members.len().min(MAX_LISTED)];
+                render_cluster_members(ui, members.len(), listed, timeline);
+                if members.len() > MAX_LISTED {
+                    ui.label(
+                        egui::RichText::new(format!("…and {} more", members.len() - MAX_LISTED))
+                            .color(Color32::from_gray(150)),
+                    );
+                }
+
+                ui.add_space(8.0);
+                ui.separator();
+                ui.add_space(4.0);
+                let hint = if separable {
+                    "💡 Click to zoom in until they separate"
+                } else {
+                    "💡 Too close together to zoom apart; click to list them all"
+                };
+                ui.label(
+                    egui::RichText::new(hint)
+                        .color(Color32::from_gray(180))
+                        .italics(),
+                );
+            });
+        });
+}
+
+/// Every member of a cluster that can't be zoomed apart, kept open until closed or until
+/// the user clicks elsewhere. Returns false once closed.
+fn render_pinned_cluster(
+    ui: &mut egui::Ui,
+    pos: Pos2,
+    members: &[&Event],
+    timeline: &Timeline,
+) -> bool {
+    let mut open = true;
+    egui::Area::new(egui::Id::new("pinned_cluster_list"))
+        .fixed_pos(egui::Pos2::new(pos.x + 15.0, pos.y + 15.0))
+        .show(ui.ctx(), |ui| {
+            cluster_frame(ui).show(ui, |ui| {
+                ui.set_max_width(300.0);
+                egui::ScrollArea::vertical()
+                    .max_height(320.0)
+                    .show(ui, |ui| {
+                        render_cluster_members(ui, members.len(), members, timeline)
+                    });
+                ui.add_space(4.0);
+                if ui.button("Close").clicked() {
+                    open = false;
+                }
+            });
+        });
+    open && !members.is_empty()
+}
+
+fn cluster_frame(ui: &egui::Ui) -> egui::Frame {
+    egui::Frame::popup(ui.style())
+        .fill(Color32::from_rgba_unmultiplied(20, 20, 20, 240))
+        .stroke(egui::Stroke::new(2.0, Color32::from_gray(120)))
+        .corner_radius(8.0)
+        .inner_margin(12.0)
+}
+
+/// A heading with the member count, then a row for each of `listed`.
+fn render_cluster_members(ui: &mut egui::Ui, count: usize, listed: &[&Event], timeline: &Timeline) {
+    ui.label(
+        egui::RichText::new(format!("{} events", count))
+            .size(16.0)
+            .color(Color32::WHITE)
+            .strong(),
+    );
+    ui.add_space(4.0);
+
+    for event in listed {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("●").color(timeline.event_color(event)));
+            ui.label(egui::RichText::new(&event.title).color(Color32::from_rgb(220, 220, 220)));
+            ui.label(
+                egui::RichText::new(format_event_date(event))
+                    .size(12.0)
+                    .color(Color32::from_gray(150))
+                    .italics(),
+            );
+        });
+    }
+}
+
+fn format_date(date: Date, precision: DatePrecision) -> String {
+    match precision {
+        DatePrecision::Year => date::format_year(date.year),
+        DatePrecision::Season => format!(
+            "{} {}",
+            date::season_name(date.month),
+            date::format_year(date.year)
+        ),
+        DatePrecision::Month => format!(
+            "{} {}",
+            get_month_name(date.month),
+            date::format_year(date.year)
+        ),
+        DatePrecision::Day | DatePrecision::Time => format!(
+            "{} {}, {}",
+            get_month_name(date.month),
+            date.day,
+            date::format_year(date.year)
+        ),
+    }
+}
+
+pub fn format_event_date(event: &Event) -> String {
+    let mut date_str = format_date(event.date(), event.precision);
+    if event.circa {
+        date_str = format!("c. {}", date_str);
+    }
+    if let Some(time) = event.time {
+        date_str.push_str(&format!(" at {}", time));
+        if let Some(timezone) = &event.timezone {
+            date_str.push_str(&format!(" ({})", timezone));
+        }
+    }
+    if let Some(end) = event.end_date {
+        date_str.push_str(&format!(" – {}", format_date(end, event.end_precision)));
+    }
+    date_str
+}
+
+pub fn get_month_name(month: u8) -> &'static str {
     match month {
         1 => "January",
         2 => "February",
@@ -470,3 +1599,20 @@ fn get_month_name(month: u8) -> &'static str {
         _ => "Unknown",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_rough_durations() {
+        assert_eq!(format_duration(30.0), "under a minute");
+        assert_eq!(format_duration(60.0), "about 1 minute");
+        assert_eq!(format_duration(3.0 * 86_400.0), "about 3 days");
+        assert_eq!(format_duration(1_380.0 * YEAR_SECONDS), "about 1,400 years");
+        assert_eq!(
+            format_duration(138_000.0 * YEAR_SECONDS),
+            "about 140,000 years"
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use crate::category::CategoryId;
+use crate::date::{self, Date};
+use crate::timeline::{Event, Timeline};
+
+/// What happens to events that don't match the search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilterDisplay {
+    #[default]
+    Dim,
+    Hide,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    /// Words that must all appear (ignoring case) in the title, description, tags or
+    /// category name
+    pub query: String,
+    pub category: Option<CategoryId>,
+    /// Anything `date::parse_year` accepts; empty means unbounded
+    pub from_year: String,
+    pub to_year: String,
+    pub display: FilterDisplay,
+}
+
+impl EventFilter {
+    pub fn is_active(&self) -> bool {
+        !self.query.trim().is_empty()
+            || self.category.is_some()
+            || self.start_bound().is_some()
+            || self.end_bound().is_some()
+    }
+
+    /// A year field that has text but doesn't parse (and so is being ignored).
+    pub fn has_invalid_year(&self) -> bool {
+        [&self.from_year, &self.to_year]
+            .iter()
+            .any(|text| !text.trim().is_empty() && date::parse_year(text).is_none())
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self {
+            display: self.display,
+            ..Self::default()
+        };
+    }
+
+    pub fn matches(&self, timeline: &Timeline, event: &Event) -> bool {
+        if self.category.is_some() && event.category != self.category {
+            return false;
+        }
+
+        // Spans match if any part of them falls inside the range. Their end is the instant
+        // right after they finish, so it doesn't count as inside.
+        let ends_before = |from: i64| match event.end_timestamp {
+            Some(end) => end <= from,
+            None => event.timestamp < from,
+        };
+        if self.start_bound().is_some_and(ends_before) {
+            return false;
+        }
+        if self.end_bound().is_some_and(|to| event.timestamp >= to) {
+            return false;
+        }
+
+        let category_name = event
+            .category
+            .and_then(|id| timeline.category(id))
+            .map(|c| c.name.as_str());
+        let fields: Vec<String> = [event.title.as_str(), event.description.as_str()]
+            .into_iter()
+            .chain(event.tags.iter().map(String::as_str))
+            .chain(category_name)
+            .map(str::to_lowercase)
+            .collect();
+
+        self.query
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .all(|word| fields.iter().any(|field| field.contains(&word)))
+    }
+
+    /// Start of the "from" year, in seconds since the epoch.
+    fn start_bound(&self) -> Option<i64> {
+        let year = date::parse_year(&self.from_year)?;
+        Some(Date::new(year, 1, 1).days_since_epoch() * date::SECONDS_PER_DAY)
+    }
+
+    /// End of the "to" year (exclusive), in seconds since the epoch.
+    fn end_bound(&self) -> Option<i64> {
+        let year = date::parse_year(&self.to_year)?;
+        Some(Date::new(year + 1, 1, 1).days_since_epoch() * date::SECONDS_PER_DAY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timeline::EventInput;
+
+    fn event(title: &str, tags: &str, category: Option<CategoryId>, year: &str) -> Event {
+        EventInput {
+            title: title.to_string(),
+            year: year.to_string(),
+            tags: tags.to_string(),
+            category,
+            ..Default::default()
+        }
+        .validate()
+        .unwrap()
+    }
+
+    fn query(query: &str) -> EventFilter {
+        EventFilter {
+            query: query.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn every_query_word_must_match_ignoring_case() {
+        let timeline = Timeline::new();
+        let event = event("Trip to Rome", "", None, "2019");
+        assert!(query("").matches(&timeline, &event));
+        assert!(query("ROME trip").matches(&timeline, &event));
+        assert!(!query("rome paris").matches(&timeline, &event));
+    }
+
+    #[test]
+    fn tags_and_category_names_count() {
+        let timeline = Timeline::new();
+        let work = timeline.categories()[0].clone();
+        let event = event("Launch", "Product, milestone", Some(work.id), "2021");
+        assert!(query("milestone").matches(&timeline, &event));
+        assert!(query(&work.name.to_uppercase()).matches(&timeline, &event));
+        assert!(query("launch product").matches(&timeline, &event));
+
+        let other = timeline.categories()[1].clone();
+        let in_other = EventFilter {
+            category: Some(other.id),
+            ..Default::default()
+        };
+        assert!(!in_other.matches(&timeline, &event));
+    }
+
+    #[test]
+    fn spans_overlapping_the_year_range_match() {
+        let timeline = Timeline::new();
+        let mut form = EventInput {
+            title: "War".to_string(),
+            year: "1939".to_string(),
+            end_year: "1945".to_string(),
+            ..Default::default()
+        };
+        let war = form.validate().unwrap();
+        let range = |from: &str, to: &str| EventFilter {
+            from_year: from.to_string(),
+            to_year: to.to_string(),
+            ..Default::default()
+        };
+
+        assert!(range("1944", "1950").matches(&timeline, &war));
+        assert!(range("1930", "1939").matches(&timeline, &war));
+        assert!(range("1941", "1942").matches(&timeline, &war));
+        assert!(range("1945", "").matches(&timeline, &war));
+        assert!(!range("1946", "").matches(&timeline, &war));
+        assert!(!range("", "1938").matches(&timeline, &war));
+
+        // A point event is just one instant
+        form.end_year.clear();
+        let point = form.validate().unwrap();
+        assert!(!range("1940", "1950").matches(&timeline, &point));
+    }
+}
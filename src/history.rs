@@ -0,0 +1,88 @@
+//! Bounded undo/redo stacks.
+
+use std::collections::VecDeque;
+
+/// How many steps can be undone; the oldest are forgotten first.
+const MAX_UNDO_STEPS: usize = 100;
+
+pub struct History<T> {
+    undo: VecDeque<T>,
+    redo: Vec<T>,
+}
+
+impl<T> History<T> {
+    pub fn new() -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Records a newly performed change. Anything that could be redone is dropped, since
+    /// it no longer follows from the current state.
+    pub fn record(&mut self, change: T) {
+        self.redo.clear();
+        self.push_undo(change);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Takes the change to undo; hand it back with [`History::push_redo`] once reverted.
+    pub fn pop_undo(&mut self) -> Option<T> {
+        self.undo.pop_back()
+    }
+
+    /// Takes the change to redo; hand it back with [`History::push_undo`] once reapplied.
+    pub fn pop_redo(&mut self) -> Option<T> {
+        self.redo.pop()
+    }
+
+    pub fn push_undo(&mut self, change: T) {
+        self.undo.push_back(change);
+        if self.undo.len() > MAX_UNDO_STEPS {
+            self.undo.pop_front();
+        }
+    }
+
+    pub fn push_redo(&mut self, change: T) {
+        self.redo.push(change);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forgets_the_oldest_steps() {
+        let mut history = History::new();
+        for step in 0..MAX_UNDO_STEPS + 5 {
+            history.record(step);
+        }
+        let mut undone = Vec::new();
+        while let Some(step) = history.pop_undo() {
+            undone.push(step);
+        }
+        assert_eq!(undone.len(), MAX_UNDO_STEPS);
+        assert_eq!(undone.last(), Some(&5));
+    }
+
+    #[test]
+    fn recording_clears_redo() {
+        let mut history = History::new();
+        history.record(1);
+        let step = history.pop_undo().unwrap();
+        history.push_redo(step);
+        assert!(history.can_redo());
+        history.record(2);
+        assert!(!history.can_redo());
+        assert_eq!(history.pop_undo(), Some(2));
+        assert!(!history.can_undo());
+    }
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+/// Stable identifier of a lane within its timeline, so renaming or moving it keeps its events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LaneId(pub u32);
+
+/// A horizontal track of its own on the canvas, e.g. "Career" or one per person.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lane {
+    pub id: LaneId,
+    pub name: String,
+    /// Shown as just its header, with its events hidden
+    #[serde(default)]
+    pub collapsed: bool,
+}
+
+/// An ID higher than any in `in_use`, which must include the IDs still on events: removing
+/// a lane leaves them there, and a new lane reusing one would claim those events. `None`
+/// once IDs run out, which only a hand-edited file can cause.
+pub fn next_id(in_use: impl IntoIterator<Item = LaneId>) -> Option<LaneId> {
+    match in_use.into_iter().map(|id| id.0).max() {
+        Some(max) => max.checked_add(1).map(LaneId),
+        None => Some(LaneId(1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_id_skips_every_id_in_use() {
+        assert_eq!(next_id([]), Some(LaneId(1)));
+        assert_eq!(next_id([LaneId(2), LaneId(7), LaneId(3)]), Some(LaneId(8)));
+        assert_eq!(next_id([LaneId(1), LaneId(u32::MAX)]), None);
+    }
+}
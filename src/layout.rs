@@ -0,0 +1,240 @@
+//! How events are arranged around the timeline axis, and which way the axis runs on screen.
+
+use eframe::epaint::{Pos2, Rect, Vec2};
+use serde::{Deserialize, Serialize};
+
+/// Saved with each timeline, since the best arrangement depends on its events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LayoutMode {
+    /// Events drift on sine waves around the axis
+    #[default]
+    Cosmic,
+    /// Events sit still in lanes above and below the axis, placed so labels don't overlap
+    Stable,
+}
+
+impl LayoutMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            LayoutMode::Cosmic => "Cosmic Drift",
+            LayoutMode::Stable => "Stable Lanes",
+        }
+    }
+}
+
+/// Vertical distance between lanes in stable mode, in points.
+pub const LANE_SPACING: f32 = 56.0;
+
+/// Where one event goes in stable mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LanePlacement {
+    /// Lanes count outwards from the axis: -1 is the first above, 1 the first below
+    pub lane: i32,
+    /// False when every lane is taken where the label would go, so it's left out
+    pub show_label: bool,
+}
+
+/// Assigns each event a lane, given the horizontal extent (left, right) it needs for its
+/// star and label, so that nothing in the same lane overlaps. Lanes nearest the axis are
+/// preferred, alternating above and below. Extents that fit nowhere go in the lane that
+/// frees up soonest, with their label hidden.
+pub fn assign_lanes(extents: &[(f32, f32)], lanes_per_side: usize) -> Vec<LanePlacement> {
+    const GAP: f32 = 8.0;
+
+    let lanes: Vec<i32> = (1..=lanes_per_side.max(1) as i32)
+        .flat_map(|n| [-n, n])
+        .collect();
+    // Right edge of the last extent placed in each lane
+    let mut lane_ends = vec![f32::NEG_INFINITY; lanes.len()];
+    let mut placements = vec![
+        LanePlacement {
+            lane: lanes[0],
+            show_label: true,
+        };
+        extents.len()
+    ];
+
+    let mut order: Vec<usize> = (0..extents.len()).collect();
+    order.sort_by(|&a, &b| extents[a].0.total_cmp(&extents[b].0));
+    for index in order {
+        let (left, right) = extents[index];
+        let free = lane_ends.iter().position(|&end| end + GAP <= left);
+        let (slot, show_label) = match free {
+            Some(slot) => (slot, true),
+            None => {
+                let soonest = lane_ends
+                    .iter()
+                    .enumerate()
+                    .min_by(|a, b| a.1.total_cmp(b.1))
+                    .map_or(0, |(slot, _)| slot);
+                (soonest, false)
+            }
+        };
+        // A star without its label is small enough to squeeze in
+        if show_label {
+            lane_ends[slot] = right;
+        }
+        placements[index] = LanePlacement {
+            lane: lanes[slot],
+            show_label,
+        };
+    }
+    placements
+}
+
+/// Events whose stars would be closer than this on screen, in points, are merged into
+/// one cluster.
+pub const CLUSTER_DISTANCE: f32 = 24.0;
+
+/// Splits ascending screen positions into runs where each position is within
+/// [`CLUSTER_DISTANCE`] of the first in its run. Returns the indices of each run.
+pub fn group_nearby(xs: &[f32]) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_start = f32::NEG_INFINITY;
+    for (index, &x) in xs.iter().enumerate() {
+        match groups.last_mut() {
+            Some(group) if x - group_start <= CLUSTER_DISTANCE => group.push(index),
+            _ => {
+                groups.push(vec![index]);
+                group_start = x;
+            }
+        }
+    }
+    groups
+}
+
+/// Which way time runs on screen. An app preference rather than part of the timeline, since
+/// it depends on the screen (a phone held upright, a tall monitor).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    /// Left to right
+    #[default]
+    Horizontal,
+    /// Top to bottom
+    Vertical,
+}
+
+impl Orientation {
+    pub fn label(self) -> &'static str {
+        match self {
+            Orientation::Horizontal => "Horizontal",
+            Orientation::Vertical => "Vertical",
+        }
+    }
+
+    /// Distance between stable lanes. Labels sit beside their star in a vertical timeline,
+    /// so its lanes are further apart.
+    pub fn lane_spacing(self) -> f32 {
+        match self {
+            Orientation::Horizontal => LANE_SPACING,
+            Orientation::Vertical => 2.0 * LANE_SPACING,
+        }
+    }
+}
+
+/// Maps between layout space, where time always runs along x, and the screen. A vertical
+/// timeline is laid out in a transposed panel and flipped about its top-left corner, so
+/// the camera, hit-testing and layout code never need to know the orientation.
+#[derive(Debug, Clone, Copy)]
+pub struct Projection {
+    pub orientation: Orientation,
+    origin: Pos2,
+}
+
+impl Projection {
+    pub fn new(orientation: Orientation, screen_rect: Rect) -> Self {
+        Self {
+            orientation,
+            origin: screen_rect.min,
+        }
+    }
+
+    /// The panel as the layout sees it.
+    pub fn layout_rect(self, screen_rect: Rect) -> Rect {
+        self.rect_to_screen(screen_rect)
+    }
+
+    pub fn to_screen(self, p: Pos2) -> Pos2 {
+        match self.orientation {
+            Orientation::Horizontal => p,
+            // Transposing is its own inverse
+            Orientation::Vertical => {
+                self.origin + Vec2::new(p.y - self.origin.y, p.x - self.origin.x)
+            }
+        }
+    }
+
+    pub fn to_layout(self, p: Pos2) -> Pos2 {
+        self.to_screen(p)
+    }
+
+    pub fn rect_to_screen(self, r: Rect) -> Rect {
+        Rect::from_two_pos(self.to_screen(r.min), self.to_screen(r.max))
+    }
+
+    /// A movement on screen (a drag, a pan key) as a movement in layout space.
+    pub fn vec_to_layout(self, v: Vec2) -> Vec2 {
+        match self.orientation {
+            Orientation::Horizontal => v,
+            Orientation::Vertical => Vec2::new(v.y, v.x),
+        }
+    }
+
+    pub fn vec_to_screen(self, v: Vec2) -> Vec2 {
+        self.vec_to_layout(v)
+    }
+
+    /// Unit vector pointing towards later times on screen.
+    pub fn time_direction(self) -> Vec2 {
+        self.vec_to_layout(Vec2::X)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lanes(placements: &[LanePlacement]) -> Vec<i32> {
+        placements.iter().map(|p| p.lane).collect()
+    }
+
+    #[test]
+    fn separate_events_share_the_nearest_lane() {
+        let placements = assign_lanes(&[(0.0, 50.0), (100.0, 150.0), (200.0, 250.0)], 2);
+        assert_eq!(lanes(&placements), vec![-1, -1, -1]);
+        assert!(placements.iter().all(|p| p.show_label));
+    }
+
+    #[test]
+    fn overlapping_events_alternate_sides() {
+        // Given out of order; lanes are still handed out from left to right
+        let placements = assign_lanes(&[(20.0, 80.0), (0.0, 60.0), (40.0, 100.0)], 2);
+        assert_eq!(lanes(&placements), vec![1, -1, -2]);
+    }
+
+    #[test]
+    fn events_that_fit_nowhere_lose_their_label() {
+        let placements = assign_lanes(&[(0.0, 100.0), (10.0, 110.0), (20.0, 120.0)], 1);
+        assert_eq!(lanes(&placements), vec![-1, 1, -1]);
+        assert_eq!(
+            placements.iter().map(|p| p.show_label).collect::<Vec<_>>(),
+            vec![true, true, false]
+        );
+    }
+
+    #[test]
+    fn groups_runs_of_nearby_positions() {
+        assert!(group_nearby(&[]).is_empty());
+        assert_eq!(
+            group_nearby(&[0.0, 10.0, 24.0, 30.0, 100.0]),
+            vec![vec![0, 1, 2], vec![3], vec![4]]
+        );
+        // Measured from the start of the run, so evenly spaced events don't chain together
+        assert_eq!(
+            group_nearby(&[0.0, 20.0, 40.0, 60.0]),
+            vec![vec![0, 1], vec![2, 3]]
+        );
+    }
+}
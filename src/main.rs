@@ -1,163 +1,1664 @@
+mod category;
+mod date;
+mod document;
 mod event_renderer;
+mod filter;
+mod history;
+mod lane;
+mod layout;
+mod migrations;
+mod minimap;
+mod recovery;
+mod settings;
 mod stars;
+#[cfg(not(target_arch = "wasm32"))]
+mod storage;
+mod time_axis;
 mod timeline;
 
+use date::Date;
+use document::Document;
 use eframe::egui;
 use eframe::epaint::Color32;
 use event_renderer::Camera;
+use layout::{LayoutMode, Orientation, Projection};
 use std::collections::HashMap;
-use timeline::{Event, Timeline};
+use timeline::{EventId, EventInput, Timeline, ValidationError};
 
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
 
-#[cfg(target_arch = "wasm32")]
-use instant::Instant;
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> eframe::Result<()> {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([800.0, 600.0])
+            .with_title("Lifeline Timeline"),
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "Lifeline",
+        options,
+        Box::new(|_cc| Ok(Box::new(LifelineApp::new()))),
+    )
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    use wasm_bindgen::JsCast;
+
+    // Make sure panics are logged using console.error
+    console_error_panic_hook::set_once();
+
+    // Redirect tracing to console.log and friends
+    tracing_wasm::set_as_global_default();
+
+    let web_options = eframe::WebOptions::default();
+
+    wasm_bindgen_futures::spawn_local(async {
+        let document = web_sys::window()
+            .expect("No window")
+            .document()
+            .expect("No document");
+
+        let canvas = document
+            .get_element_by_id("the_canvas_id")
+            .expect("Failed to find the_canvas_id")
+            .dyn_into::<web_sys::HtmlCanvasElement>()
+            .expect("the_canvas_id was not a HtmlCanvasElement");
+
+        let start_result = eframe::WebRunner::new()
+            .start(
+                canvas,
+                web_options,
+                Box::new(|_cc| Ok(Box::new(LifelineApp::new()))),
+            )
+            .await;
+
+        // Remove loading spinner
+        if let Some(loading) = document.get_element_by_id("loading") {
+            loading.remove();
+        }
+
+        start_result.expect("failed to start eframe");
+    });
+}
+
+struct LifelineApp {
+    stars: Vec<stars::Star>,
+    galaxies: Vec<stars::Galaxy>,
+    nebulas: Vec<stars::Nebula>,
+    start_time: Instant,
+    timeline: Timeline,
+    camera: Camera,
+    // Click-to-stop state
+    clicked_event_id: Option<EventId>,
+    frozen_positions: HashMap<EventId, (f32, f32)>,
+    resume_start_times: HashMap<EventId, f32>,
+    // Deletion state
+    event_to_delete: Option<EventId>,
+    // Waiting for the user to confirm moving this event to the trash
+    pending_delete: Option<EventId>,
+    show_trash: bool,
+    settings: settings::Settings,
+    // Palette being edited in the Categories window; applied as one undoable change
+    category_draft: Option<Vec<category::Category>>,
+    // Lanes being edited in the Lanes window, likewise
+    lane_draft: Option<Vec<lane::Lane>>,
+    // Search panel; the filter only applies while it's open
+    show_search: bool,
+    filter: filter::EventFilter,
+    // Screen area the events were last drawn in, for moving the camera to an event
+    timeline_rect: egui::Rect,
+    // Last event flown to, for next/previous navigation; cleared when the user pans
+    focused_event_id: Option<EventId>,
+    // Set while a mouse or touch drag is moving the view
+    drag_panning: bool,
+    // UI state for adding events
+    event_form: EventInput,
+    form_errors: Vec<ValidationError>,
+    // Set while the form is editing an existing event instead of adding a new one
+    editing_event_id: Option<EventId>,
+    show_add_panel: bool,
+    // Image cache
+    image_cache: HashMap<String, egui::TextureHandle>,
+    // Document (file) state
+    document: Document,
+    #[cfg(not(target_arch = "wasm32"))]
+    recent_files: document::RecentFiles,
+    pending_discard: Option<DocumentAction>,
+    document_error: Option<String>,
+    window_title: String,
+    // Saved data that failed to load, and the result of trying to recover it
+    corrupt_data: Option<recovery::CorruptData>,
+    recovery_outcome: Option<String>,
+}
+
+/// Lets the user pick a timeline file, and stores its name and contents under `key` (and
+/// `{key}_name`) in localStorage for the next frame to pick up, like uploaded images.
+#[cfg(target_arch = "wasm32")]
+fn pick_timeline_file(key: &'static str) {
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Some(file) = rfd::AsyncFileDialog::new()
+            .add_filter("Lifeline timeline", &[document::FILE_EXTENSION])
+            .pick_file()
+            .await
+        {
+            let data = file.read().await;
+            let json = String::from_utf8_lossy(&data).into_owned();
+
+            if let Some(window) = web_sys::window() {
+                if let Ok(Some(storage)) = window.local_storage() {
+                    let _ = storage.set_item(key, &json);
+                    let _ = storage.set_item(&format!("{}_name", key), &file.file_name());
+                }
+            }
+        }
+    });
+}
+
+/// Counterpart of [`pick_timeline_file`]: returns and clears the file name and contents.
+#[cfg(target_arch = "wasm32")]
+fn take_picked_file(key: &str) -> Option<(String, String)> {
+    let json = take_item(key)?;
+    let file_name = take_item(&format!("{}_name", key)).unwrap_or_default();
+    Some((file_name, json))
+}
+
+/// Stores `value` under `key` in localStorage, for passing a result from an async task to
+/// the next frame.
+#[cfg(target_arch = "wasm32")]
+fn store_item(key: &str, value: &str) {
+    if let Some(window) = web_sys::window() {
+        if let Ok(Some(storage)) = window.local_storage() {
+            let _ = storage.set_item(key, value);
+        }
+    }
+}
+
+/// Counterpart of [`store_item`]: returns and clears the value.
+#[cfg(target_arch = "wasm32")]
+fn take_item(key: &str) -> Option<String> {
+    let storage = web_sys::window()?.local_storage().ok()??;
+    let value = storage.get_item(key).ok()??;
+    let _ = storage.remove_item(key);
+    Some(value)
+}
+
+/// `YYYY-MM-DD` (UTC) for a Unix timestamp.
+fn format_calendar_day(seconds: i64) -> String {
+    let day = Date::from_days_since_epoch(seconds.div_euclid(date::SECONDS_PER_DAY));
+    format!("{}-{:02}-{:02}", day.year, day.month, day.day)
+}
+
+const SEARCH_FIELD_ID: &str = "search_field";
+
+/// Document actions that replace the open timeline, and so need confirmation when
+/// there are unsaved changes.
+#[derive(Debug, Clone)]
+enum DocumentAction {
+    New,
+    Open,
+    #[cfg(not(target_arch = "wasm32"))]
+    OpenRecent(std::path::PathBuf),
+}
+
+impl LifelineApp {
+    #[cfg(target_arch = "wasm32")]
+    fn save_to_storage(&self) {
+        if let Ok(json) = self.timeline.to_json() {
+            if let Some(window) = web_sys::window() {
+                if let Ok(Some(storage)) = window.local_storage() {
+                    let _ = storage.set_item("lifeline_events", &json);
+                }
+            }
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn load_from_storage() -> (Timeline, Option<recovery::CorruptData>) {
+        if let Some(window) = web_sys::window() {
+            if let Ok(Some(storage)) = window.local_storage() {
+                if let Ok(Some(json)) = storage.get_item("lifeline_events") {
+                    match Timeline::from_json(&json) {
+                        Ok(timeline) => return (timeline, None),
+                        Err(err) => {
+                            // Keep the unreadable data where the next save can't overwrite it
+                            let backed_up =
+                                storage.set_item("lifeline_events_backup", &json).is_ok();
+                            let mut data = recovery::CorruptData::new(json, &err);
+                            data.backed_up = backed_up;
+                            return (Timeline::new(), Some(data));
+                        }
+                    }
+                }
+            }
+        }
+        (Timeline::new(), None)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn save_document_state(&self) {
+        if let Ok(json) = serde_json::to_string(&self.document) {
+            if let Some(window) = web_sys::window() {
+                if let Ok(Some(storage)) = window.local_storage() {
+                    let _ = storage.set_item("lifeline_document", &json);
+                }
+            }
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn load_document_state(timeline: &Timeline) -> Document {
+        if let Some(window) = web_sys::window() {
+            if let Ok(Some(storage)) = window.local_storage() {
+                if let Ok(Some(json)) = storage.get_item("lifeline_document") {
+                    if let Ok(mut document) = serde_json::from_str::<Document>(&json) {
+                        document.refresh_dirty(timeline);
+                        return document;
+                    }
+                }
+            }
+        }
+        let mut document = Document::untitled(&Timeline::new());
+        document.refresh_dirty(timeline);
+        document
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_to_storage(&self) {
+        let Some(path) = storage::timeline_path() else {
+            return;
+        };
+        let result = self
+            .timeline
+            .to_json()
+            .map_err(std::io::Error::from)
+            .and_then(|json| storage::write_atomically(&path, &json));
+        if let Err(err) = result {
+            eprintln!("Failed to save timeline to {}: {}", path.display(), err);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_from_storage() -> (Timeline, Option<recovery::CorruptData>) {
+        let Some(path) = storage::timeline_path() else {
+            return (Timeline::new(), None);
+        };
+        let bytes = match storage::read_bytes_if_exists(&path) {
+            Ok(Some(bytes)) => bytes,
+            Ok(None) => return (Timeline::new(), None),
+            Err(err) => {
+                eprintln!("Failed to read timeline at {}: {}", path.display(), err);
+                return (
+                    Timeline::new(),
+                    Some(recovery::CorruptData::unreadable(&err)),
+                );
+            }
+        };
+        let mut data = match std::str::from_utf8(&bytes) {
+            Ok(json) => match Timeline::from_json(json) {
+                Ok(timeline) => return (timeline, None),
+                Err(err) => recovery::CorruptData::new(json.to_string(), &err),
+            },
+            Err(err) => recovery::CorruptData::invalid_utf8(&bytes, err),
+        };
+        eprintln!(
+            "Failed to parse timeline at {}: {}",
+            path.display(),
+            data.message
+        );
+
+        // Keep the unreadable data, byte for byte, where the next save can't overwrite it
+        if let Some(backup) = storage::backup_path() {
+            match storage::write_atomically(&backup, &bytes) {
+                Ok(()) => data.backed_up = true,
+                Err(err) => eprintln!(
+                    "Failed to back up timeline to {}: {}",
+                    backup.display(),
+                    err
+                ),
+            }
+        }
+        (Timeline::new(), Some(data))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_document_state(&self) {
+        let files = [
+            (
+                storage::document_path(),
+                serde_json::to_string(&self.document),
+            ),
+            (
+                storage::recent_files_path(),
+                serde_json::to_string(&self.recent_files),
+            ),
+        ];
+        for (path, json) in files {
+            let Some(path) = path else {
+                continue;
+            };
+            let result = json
+                .map_err(std::io::Error::from)
+                .and_then(|json| storage::write_atomically(&path, &json));
+            if let Err(err) = result {
+                eprintln!("Failed to save {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_document_state(timeline: &Timeline) -> (Document, document::RecentFiles) {
+        fn read<T: serde::de::DeserializeOwned>(path: Option<std::path::PathBuf>) -> Option<T> {
+            let json = storage::read_if_exists(&path?).ok()??;
+            serde_json::from_str(&json).ok()
+        }
+
+        let mut document = read::<Document>(storage::document_path())
+            .unwrap_or_else(|| Document::untitled(&Timeline::new()));
+        document.refresh_dirty(timeline);
+        let recent_files = read(storage::recent_files_path()).unwrap_or_default();
+        (document, recent_files)
+    }
+
+    fn new() -> Self {
+        // Generate background cosmic objects
+        let stars = stars::generate_stars(150);
+        let galaxies = stars::generate_galaxies(4);
+        let nebulas = stars::generate_nebulas(3);
+
+        // Load the saved timeline (localStorage on the web, a data file on desktop)
+        let (timeline, corrupt_data) = Self::load_from_storage();
+
+        #[cfg(target_arch = "wasm32")]
+        let document = Self::load_document_state(&timeline);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let (document, recent_files) = Self::load_document_state(&timeline);
+
+        Self {
+            stars,
+            galaxies,
+            nebulas,
+            start_time: Instant::now(),
+            timeline,
+            camera: Camera::new(),
+            clicked_event_id: None,
+            frozen_positions: HashMap::new(),
+            resume_start_times: HashMap::new(),
+            event_to_delete: None,
+            pending_delete: None,
+            show_trash: false,
+            settings: settings::Settings::load(),
+            category_draft: None,
+            lane_draft: None,
+            show_search: false,
+            filter: filter::EventFilter::default(),
+            timeline_rect: egui::Rect::NOTHING,
+            focused_event_id: None,
+            drag_panning: false,
+            event_form: EventInput::default(),
+            form_errors: Vec::new(),
+            editing_event_id: None,
+            show_add_panel: false,
+            image_cache: HashMap::new(),
+            document,
+            #[cfg(not(target_arch = "wasm32"))]
+            recent_files,
+            pending_discard: None,
+            document_error: None,
+            window_title: String::new(),
+            corrupt_data,
+            recovery_outcome: None,
+        }
+    }
+
+    /// Call after every edit: autosaves the working copy and updates the dirty marker.
+    fn timeline_changed(&mut self) {
+        self.save_to_storage();
+        self.document.refresh_dirty(&self.timeline);
+    }
+
+    /// Swaps in another timeline (New/Open), dropping all per-event UI state.
+    fn replace_timeline(&mut self, timeline: Timeline, document: Document) {
+        self.timeline = timeline;
+        self.document = document;
+        self.clicked_event_id = None;
+        self.frozen_positions.clear();
+        self.resume_start_times.clear();
+        self.event_to_delete = None;
+        self.pending_delete = None;
+        self.category_draft = None;
+        self.lane_draft = None;
+        if self.editing_event_id.is_some() {
+            self.stop_editing();
+        }
+        self.save_to_storage();
+        self.save_document_state();
+    }
+
+    fn request_document_action(&mut self, action: DocumentAction) {
+        if self.document.is_dirty() {
+            self.pending_discard = Some(action);
+        } else {
+            self.perform_document_action(action);
+        }
+    }
+
+    fn perform_document_action(&mut self, action: DocumentAction) {
+        match action {
+            DocumentAction::New => {
+                let timeline = Timeline::new();
+                let document = Document::untitled(&timeline);
+                self.replace_timeline(timeline, document);
+            }
+            DocumentAction::Open => self.open_document_dialog(),
+            #[cfg(not(target_arch = "wasm32"))]
+            DocumentAction::OpenRecent(path) => self.open_document_path(&path),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open_document_dialog(&mut self) {
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("Lifeline timeline", &[document::FILE_EXTENSION])
+            .pick_file()
+        {
+            self.open_document_path(&path);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open_document_path(&mut self, path: &std::path::Path) {
+        match document::open_file(path) {
+            Ok((timeline, document)) => {
+                self.recent_files.push(path);
+                self.replace_timeline(timeline, document);
+            }
+            Err(err) => {
+                self.recent_files.remove(path);
+                self.save_document_state();
+                self.document_error = Some(format!("Could not open {}: {}", path.display(), err));
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn import_dialog(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Lifeline timeline", &[document::FILE_EXTENSION])
+            .pick_file()
+        else {
+            return;
+        };
+        match document::open_file(&path) {
+            Ok((imported, _)) => self.import_events(imported),
+            Err(err) => {
+                self.document_error = Some(format!("Could not import {}: {}", path.display(), err));
+            }
+        }
+    }
+
+    /// Adds another timeline's events to this one, as a single undo step.
+    fn import_events(&mut self, imported: Timeline) {
+        self.timeline.import_events(
+            imported.events().to_vec(),
+            imported.categories(),
+            imported.lanes(),
+        );
+        self.timeline_changed();
+    }
+
+    fn undo(&mut self) {
+        if self.timeline.undo() {
+            self.history_changed();
+        }
+    }
+
+    fn redo(&mut self) {
+        if self.timeline.redo() {
+            self.history_changed();
+        }
+    }
+
+    /// After undo/redo: drops UI state of events that no longer exist and saves.
+    fn history_changed(&mut self) {
+        let timeline = &self.timeline;
+        self.frozen_positions
+            .retain(|id, _| timeline.event(*id).is_some());
+        self.resume_start_times
+            .retain(|id, _| timeline.event(*id).is_some());
+        if self
+            .clicked_event_id
+            .is_some_and(|id| timeline.event(id).is_none())
+        {
+            self.clicked_event_id = None;
+        }
+        if self
+            .editing_event_id
+            .is_some_and(|id| timeline.event(id).is_none())
+        {
+            self.stop_editing();
+        }
+        self.timeline_changed();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_document(&mut self) {
+        match self.document.path.clone() {
+            Some(path) => self.save_document_to(&path),
+            None => self.save_document_as(),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_document_as(&mut self) {
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("Lifeline timeline", &[document::FILE_EXTENSION])
+            .set_file_name(format!(
+                "{}.{}",
+                self.document.name,
+                document::FILE_EXTENSION
+            ))
+            .save_file()
+        {
+            self.save_document_to(&path);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_document_to(&mut self, path: &std::path::Path) {
+        // The timeline takes the file's name, so it keeps it when shared
+        let name = document::display_name(&path.file_name().unwrap_or_default().to_string_lossy());
+        self.timeline.set_name(name);
+
+        match document::save_file(path, &self.timeline) {
+            Ok(document) => {
+                self.document = document;
+                self.recent_files.push(path);
+                self.save_to_storage();
+                self.save_document_state();
+            }
+            Err(err) => {
+                self.document_error = Some(format!("Could not save {}: {}", path.display(), err));
+            }
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn open_document_dialog(&mut self) {
+        pick_timeline_file("lifeline_temp_document");
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn import_dialog(&mut self) {
+        pick_timeline_file("lifeline_temp_import");
+    }
+
+    /// Browsers can't write files in place, so Save always downloads a copy.
+    #[cfg(target_arch = "wasm32")]
+    fn save_document(&mut self) {
+        self.save_document_as();
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn save_document_as(&mut self) {
+        self.timeline.set_name(self.document.name.clone());
+        let json = match self.timeline.to_json() {
+            Ok(json) => json,
+            Err(err) => {
+                self.document_error = Some(format!("Could not save: {}", err));
+                return;
+            }
+        };
+        // Only counts as saved once the download is written; a cancelled dialog leaves the
+        // document dirty. The outcome is picked up by `receive_picked_files`.
+        let saved = Document::saved_as(None, self.document.name.clone(), &self.timeline);
+        let Ok(saved) = serde_json::to_string(&saved) else {
+            return;
+        };
+        let file_name = format!("{}.{}", self.document.name, document::FILE_EXTENSION);
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Some(file) = rfd::AsyncFileDialog::new()
+                .set_file_name(&file_name)
+                .save_file()
+                .await
+            {
+                match file.write(json.as_bytes()).await {
+                    Ok(()) => store_item("lifeline_temp_saved", &saved),
+                    Err(err) => store_item(
+                        "lifeline_temp_save_error",
+                        &format!("Could not save {}: {}", file_name, err),
+                    ),
+                }
+            }
+        });
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn receive_picked_files(&mut self) {
+        if let Some(saved) = take_item("lifeline_temp_saved") {
+            if let Ok(mut document) = serde_json::from_str::<Document>(&saved) {
+                // Edits made while the dialog was open are still unsaved
+                document.refresh_dirty(&self.timeline);
+                self.document = document;
+                self.save_document_state();
+            }
+        }
+        if let Some(error) = take_item("lifeline_temp_save_error") {
+            self.document_error = Some(error);
+        }
+
+        if let Some((file_name, json)) = take_picked_file("lifeline_temp_import") {
+            match Timeline::from_json(&json) {
+                Ok(imported) => self.import_events(imported),
+                Err(err) => {
+                    self.document_error = Some(format!(
+                        "Could not import {}: {}",
+                        file_name,
+                        document::DocumentError::Parse(err)
+                    ));
+                }
+            }
+        }
+
+        let Some((file_name, json)) = take_picked_file("lifeline_temp_document") else {
+            return;
+        };
+        match Timeline::from_json(&json) {
+            Ok(timeline) => {
+                let document =
+                    Document::saved_as(None, document::display_name(&file_name), &timeline);
+                self.replace_timeline(timeline, document);
+            }
+            Err(err) => {
+                self.document_error = Some(format!(
+                    "Could not open {}: {}",
+                    file_name,
+                    document::DocumentError::Parse(err)
+                ));
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_corrupt_data(&mut self) {
+        let Some(data) = &self.corrupt_data else {
+            return;
+        };
+        if let Some(path) = rfd::FileDialog::new()
+            .set_file_name("lifeline-backup.json")
+            .save_file()
+        {
+            if let Err(err) = storage::write_atomically(&path, &data.raw) {
+                self.recovery_outcome =
+                    Some(format!("Could not export to {}: {}", path.display(), err));
+            }
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn export_corrupt_data(&mut self) {
+        let Some(data) = &self.corrupt_data else {
+            return;
+        };
+        let raw = data.raw.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Some(file) = rfd::AsyncFileDialog::new()
+                .set_file_name("lifeline-backup.json")
+                .save_file()
+                .await
+            {
+                let _ = file.write(raw.as_bytes()).await;
+            }
+        });
+    }
+
+    fn recover_partially(&mut self) {
+        let Some(data) = &self.corrupt_data else {
+            return;
+        };
+        let (timeline, skipped) = Timeline::from_json_lenient(&data.raw);
+        let recovered = timeline.events().len();
+
+        let document = self.document.clone();
+        self.replace_timeline(timeline, document);
+        self.document.refresh_dirty(&self.timeline);
+
+        self.recovery_outcome = Some(if skipped == 0 {
+            format!("Recovered all {} events.", recovered)
+        } else {
+            format!(
+                "Recovered {} events; {} could not be read and were left out.",
+                recovered, skipped
+            )
+        });
+    }
+
+    fn show_delete_confirmation(&mut self, ctx: &egui::Context) {
+        let Some(id) = self.pending_delete else {
+            return;
+        };
+        let Some(event) = self.timeline.event(id) else {
+            self.pending_delete = None;
+            return;
+        };
+
+        let mut choice = None;
+        let mut dont_ask = !self.settings.confirm_delete;
+        egui::Window::new("Delete event")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("Move \"{}\" to the Trash?", event.title));
+                ui.label(
+                    egui::RichText::new("You can restore it from Edit → Trash.")
+                        .color(Color32::from_gray(180)),
+                );
+                ui.add_space(8.0);
+                ui.checkbox(&mut dont_ask, "Don't ask again");
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("🗑 Move to Trash").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        choice = Some(false);
+                    }
+                });
+            });
+
+        if dont_ask == self.settings.confirm_delete {
+            self.settings.confirm_delete = !dont_ask;
+            self.settings.save();
+        }
+        match choice {
+            Some(true) => {
+                self.event_to_delete = Some(id);
+                self.pending_delete = None;
+            }
+            Some(false) => self.pending_delete = None,
+            None => {}
+        }
+    }
+
+    /// Maps between the screen and the layout space the camera works in.
+    fn projection(&self) -> Projection {
+        Projection::new(self.settings.orientation, self.timeline_rect)
+    }
+
+    fn timeline_layout(&self) -> Option<event_renderer::TimelineLayout> {
+        event_renderer::TimelineLayout::new(
+            &self.timeline,
+            self.timeline_rect,
+            self.settings.orientation,
+        )
+    }
+
+    /// Middle of the view, in layout space.
+    fn view_center(&self) -> egui::Pos2 {
+        self.projection().to_layout(self.timeline_rect.center())
+    }
+
+    /// Flies the camera so the event is in the middle of the view, zooming in if the view is
+    /// zoomed out.
+    fn focus_event(&mut self, id: EventId) {
+        let Some(event) = self.timeline.event(id) else {
+            return;
+        };
+        let Some(layout) = self.timeline_layout() else {
+            return;
+        };
+        let zoom_range = self.settings.zoom_range();
+        self.camera.fly_to(
+            layout.event_center(event),
+            self.view_center(),
+            self.camera
+                .zoom
+                .max(1.0)
+                .clamp(*zoom_range.start(), *zoom_range.end()),
+            self.start_time.elapsed().as_secs_f32(),
+        );
+        self.focused_event_id = Some(id);
+    }
+
+    /// Flies the camera to where today falls on the timeline.
+    fn focus_today(&mut self) {
+        let Some(layout) = self.timeline_layout() else {
+            return;
+        };
+        let today = date::instant_seconds(Date::today(), None, None);
+        let zoom_range = self.settings.zoom_range();
+        self.camera.fly_to(
+            egui::Pos2::new(layout.x(today), layout.center_y),
+            self.view_center(),
+            self.camera
+                .zoom
+                .clamp(*zoom_range.start(), *zoom_range.end()),
+            self.start_time.elapsed().as_secs_f32(),
+        );
+        self.focused_event_id = None;
+    }
+
+    /// Flies to the next (or previous) event after the focused one, or after the middle of
+    /// the view if none is focused. Only events matching an active search count.
+    fn focus_adjacent_event(&mut self, forward: bool) {
+        let Some(layout) = self.timeline_layout() else {
+            return;
+        };
+        let filter = (self.show_search && self.filter.is_active()).then_some(&self.filter);
+        let candidates: Vec<&timeline::Event> = self
+            .timeline
+            .events()
+            .iter()
+            .filter(|e| filter.is_none_or(|f| f.matches(&self.timeline, e)))
+            .collect();
+
+        let focused = self
+            .focused_event_id
+            .and_then(|id| candidates.iter().position(|e| e.id == id));
+        let target = match focused {
+            Some(index) if forward => candidates.get(index + 1),
+            Some(index) => index.checked_sub(1).and_then(|i| candidates.get(i)),
+            None => {
+                let center = self.view_center().x;
+                let view_x = (center - self.camera.offset_x) / self.camera.zoom;
+                let x = |e: &&timeline::Event| layout.event_center(e).x;
+                if forward {
+                    candidates.iter().find(|e| x(e) > view_x + 1.0)
+                } else {
+                    candidates.iter().rev().find(|e| x(e) < view_x - 1.0)
+                }
+            }
+        };
+
+        if let Some(id) = target.map(|e| e.id) {
+            self.focus_event(id);
+        }
+    }
+
+    fn show_search_panel(&mut self, ctx: &egui::Context) {
+        if !self.show_search {
+            return;
+        }
+
+        let mut jump_to = None;
+        egui::SidePanel::right("search_panel")
+            .resizable(false)
+            .exact_width(280.0)
+            .frame(
+                egui::Frame::new()
+                    .fill(Color32::from_rgba_unmultiplied(0, 0, 0, 200))
+                    .inner_margin(10.0),
+            )
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("🔍 Search");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("✖").on_hover_text("Close").clicked() {
+                            self.show_search = false;
+                        }
+                    });
+                });
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.filter.query)
+                        .id(egui::Id::new(SEARCH_FIELD_ID))
+                        .hint_text("Title, description, tags...")
+                        .desired_width(f32::INFINITY),
+                );
+
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.label("Category:");
+                    let selected = self
+                        .filter
+                        .category
+                        .and_then(|id| self.timeline.category(id))
+                        .map_or("Any".to_string(), |c| c.name.clone());
+                    egui::ComboBox::from_id_salt("filter_category")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.filter.category, None, "Any");
+                            for category in self.timeline.categories() {
+                                ui.selectable_value(
+                                    &mut self.filter.category,
+                                    Some(category.id),
+                                    egui::RichText::new(format!("● {}", category.name))
+                                        .color(category.color32()),
+                                );
+                            }
+                        });
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("From:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.filter.from_year)
+                            .hint_text("year")
+                            .desired_width(70.0),
+                    );
+                    ui.label("to:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.filter.to_year)
+                            .hint_text("year")
+                            .desired_width(70.0),
+                    );
+                });
+                if self.filter.has_invalid_year() {
+                    ui.label(
+                        egui::RichText::new("⚠ Years look like 1990, 44 BCE or 66 Ma")
+                            .color(Color32::from_rgb(255, 110, 110)),
+                    );
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Others:");
+                    ui.radio_value(&mut self.filter.display, filter::FilterDisplay::Dim, "Dim");
+                    ui.radio_value(
+                        &mut self.filter.display,
+                        filter::FilterDisplay::Hide,
+                        "Hide",
+                    );
+                    if ui.button("Clear").clicked() {
+                        self.filter.clear();
+                    }
+                });
+
+                ui.separator();
+
+                let results: Vec<&timeline::Event> = self
+                    .timeline
+                    .events()
+                    .iter()
+                    .filter(|e| self.filter.matches(&self.timeline, e))
+                    .collect();
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{} of {} events",
+                        results.len(),
+                        self.timeline.events().len()
+                    ))
+                    .color(Color32::from_gray(180)),
+                );
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for event in results {
+                        let color = self.timeline.event_color(event);
+                        let text = egui::RichText::new(format!("● {}", event.title)).color(color);
+                        let response = ui.selectable_label(false, text);
+                        ui.label(
+                            egui::RichText::new(event_renderer::format_event_date(event))
+                                .small()
+                                .color(Color32::from_gray(160)),
+                        );
+                        if response.clicked() {
+                            jump_to = Some(event.id);
+                        }
+                    }
+                });
+            });
+
+        if let Some(id) = jump_to {
+            self.focus_event(id);
+        }
+    }
+
+    fn open_category_editor(&mut self) {
+        if self.category_draft.is_none() {
+            self.category_draft = Some(self.timeline.categories().to_vec());
+        }
+    }
+
+    fn show_category_editor(&mut self, ctx: &egui::Context) {
+        let Some(draft) = &mut self.category_draft else {
+            return;
+        };
+
+        let mut open = true;
+        let mut apply = false;
+        let mut cancel = false;
+        let mut remove = None;
+
+        egui::Window::new("🎨 Categories")
+            .open(&mut open)
+            .default_width(300.0)
+            .show(ctx, |ui| {
+                for (index, category) in draft.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.color_edit_button_srgb(&mut category.color);
+                        ui.add(egui::TextEdit::singleline(&mut category.name).desired_width(160.0));
+                        if ui
+                            .small_button("✖")
+                            .on_hover_text("Remove; its events keep their own colour")
+                            .clicked()
+                        {
+                            remove = Some(index);
+                        }
+                    });
+                }
+
+                let in_use = draft.iter().map(|c| c.id);
+                let next_id = category::next_id(in_use.chain(self.timeline.category_ids_in_use()));
+                if ui
+                    .add_enabled(next_id.is_some(), egui::Button::new("➕ Add Category"))
+                    .clicked()
+                {
+                    if let Some(id) = next_id {
+                        draft.push(category::Category {
+                            id,
+                            name: "New category".to_string(),
+                            color: [200, 200, 200],
+                        });
+                    }
+                }
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Apply").clicked() {
+                        apply = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if let Some(index) = remove {
+            draft.remove(index);
+        }
+        if apply {
+            let categories = self.category_draft.take().unwrap_or_default();
+            self.timeline.set_categories(categories);
+            self.timeline_changed();
+        } else if cancel || !open {
+            self.category_draft = None;
+        }
+    }
+
+    fn open_lane_editor(&mut self) {
+        if self.lane_draft.is_none() {
+            self.lane_draft = Some(self.timeline.lanes().to_vec());
+        }
+    }
+
+    fn show_lane_editor(&mut self, ctx: &egui::Context) {
+        let Some(draft) = &mut self.lane_draft else {
+            return;
+        };
+
+        let mut open = true;
+        let mut apply = false;
+        let mut cancel = false;
+        let mut remove = None;
+        let mut move_up = None;
+
+        egui::Window::new("☰ Lanes")
+            .open(&mut open)
+            .default_width(300.0)
+            .show(ctx, |ui| {
+                if draft.is_empty() {
+                    ui.label(
+                        egui::RichText::new("No lanes: every event shares one track.")
+                            .color(Color32::from_gray(160))
+                            .italics(),
+                    );
+                }
+                let count = draft.len();
+                for (index, lane) in draft.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(index > 0, egui::Button::new("⏶").small())
+                            .on_hover_text("Move up")
+                            .clicked()
+                        {
+                            move_up = Some(index);
+                        }
+                        if ui
+                            .add_enabled(index + 1 < count, egui::Button::new("⏷").small())
+                            .on_hover_text("Move down")
+                            .clicked()
+                        {
+                            move_up = Some(index + 1);
+                        }
+                        ui.add(egui::TextEdit::singleline(&mut lane.name).desired_width(160.0));
+                        if ui
+                            .small_button("✖")
+                            .on_hover_text("Remove; its events move to the unassigned track")
+                            .clicked()
+                        {
+                            remove = Some(index);
+                        }
+                    });
+                }
+
+                let in_use = draft.iter().map(|l| l.id);
+                let next_id = lane::next_id(in_use.chain(self.timeline.lane_ids_in_use()));
+                if ui
+                    .add_enabled(next_id.is_some(), egui::Button::new("➕ Add Lane"))
+                    .clicked()
+                {
+                    if let Some(id) = next_id {
+                        draft.push(lane::Lane {
+                            id,
+                            name: "New lane".to_string(),
+                            collapsed: false,
+                        });
+                    }
+                }
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Apply").clicked() {
+                        apply = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if let Some(index) = move_up {
+            draft.swap(index - 1, index);
+        }
+        if let Some(index) = remove {
+            draft.remove(index);
+        }
+        if apply {
+            let lanes = self.lane_draft.take().unwrap_or_default();
+            self.timeline.set_lanes(lanes);
+            self.timeline_changed();
+        } else if cancel || !open {
+            self.lane_draft = None;
+        }
+    }
+
+    fn show_trash_window(&mut self, ctx: &egui::Context) {
+        if !self.show_trash {
+            return;
+        }
+
+        let mut open = true;
+        let mut restore = None;
+        let mut purge = None;
+        let mut empty = false;
+        let mut confirm_delete = self.settings.confirm_delete;
+
+        egui::Window::new("🗑 Trash")
+            .open(&mut open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                let trash = self.timeline.trash();
+                if trash.is_empty() {
+                    ui.label(
+                        egui::RichText::new("The Trash is empty.").color(Color32::from_gray(180)),
+                    );
+                }
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        // Most recently deleted first
+                        for entry in trash.iter().rev() {
+                            let event = &entry.event;
+                            ui.horizontal(|ui| {
+                                ui.vertical(|ui| {
+                                    ui.label(egui::RichText::new(&event.title).strong());
+                                    ui.label(
+                                        egui::RichText::new(format!(
+                                            "{} · deleted {}",
+                                            event_renderer::format_event_date(event),
+                                            format_calendar_day(entry.deleted)
+                                        ))
+                                        .small()
+                                        .color(Color32::from_gray(180)),
+                                    );
+                                });
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        if ui
+                                            .button("Delete Forever")
+                                            .on_hover_text("Remove it permanently")
+                                            .clicked()
+                                        {
+                                            purge = Some(event.id);
+                                        }
+                                        if ui.button("Restore").clicked() {
+                                            restore = Some(event.id);
+                                        }
+                                    },
+                                );
+                            });
+                            ui.separator();
+                        }
+                    });
+
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!trash.is_empty(), egui::Button::new("Empty Trash"))
+                        .clicked()
+                    {
+                        empty = true;
+                    }
+                    ui.checkbox(&mut confirm_delete, "Ask before moving events to the Trash");
+                });
+            });
+
+        self.show_trash = open;
+        if confirm_delete != self.settings.confirm_delete {
+            self.settings.confirm_delete = confirm_delete;
+            self.settings.save();
+        }
+
+        let changed = match (restore, purge) {
+            (Some(id), _) => self.timeline.restore_event(id),
+            (_, Some(id)) => self.timeline.purge_event(id),
+            _ if empty => {
+                self.timeline.empty_trash();
+                true
+            }
+            _ => false,
+        };
+        if changed {
+            self.timeline_changed();
+        }
+    }
+
+    fn show_recovery_dialog(&mut self, ctx: &egui::Context) {
+        let Some(data) = &self.corrupt_data else {
+            return;
+        };
+
+        let mut export = false;
+        let mut partial_load = false;
+        let mut close = false;
+
+        egui::Window::new("Saved timeline could not be loaded")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(data.explanation());
+                ui.add_space(4.0);
+                // Errors found after parsing (such as an impossible date) have no position
+                let error = if data.line == 0 {
+                    data.message.clone()
+                } else {
+                    format!(
+                        "Line {}, column {}: {}",
+                        data.line, data.column, data.message
+                    )
+                };
+                ui.label(egui::RichText::new(error).color(Color32::from_rgb(255, 100, 100)));
+                if let Some(excerpt) = data.excerpt() {
+                    ui.label(egui::RichText::new(excerpt).monospace());
+                }
+                ui.add_space(8.0);
+
+                if let Some(outcome) = &self.recovery_outcome {
+                    ui.label(outcome);
+                    ui.add_space(8.0);
+                    if ui.button("OK").clicked() {
+                        close = true;
+                    }
+                    return;
+                }
+
+                #[cfg(not(target_arch = "wasm32"))]
+                let backup_location = storage::backup_path()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_else(|| "the data directory".to_string());
+                #[cfg(target_arch = "wasm32")]
+                let backup_location = "browser storage".to_string();
+                if data.backed_up {
+                    ui.label(format!(
+                        "A copy of the original data has been kept in {}.",
+                        backup_location
+                    ));
+                } else {
+                    ui.label(
+                        "No copy of the original data could be made. Starting empty and then \
+                         editing will overwrite it.",
+                    );
+                }
+                ui.add_space(8.0);
+
+                ui.horizontal(|ui| {
+                    // Nothing to export or pick events from when the file couldn't be read
+                    if !data.raw.is_empty() {
+                        if ui.button("💾 Export Raw Text").clicked() {
+                            export = true;
+                        }
+                        if ui
+                            .button("🩹 Load What Can Be Read")
+                            .on_hover_text("Keep every event that still parses and skip the rest")
+                            .clicked()
+                        {
+                            partial_load = true;
+                        }
+                    }
+                    if ui.button("Start Empty").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if export {
+            self.export_corrupt_data();
+        }
+        if partial_load {
+            self.recover_partially();
+        }
+        if close {
+            self.corrupt_data = None;
+            self.recovery_outcome = None;
+        }
+    }
+
+    fn update_window_title(&mut self, ctx: &egui::Context) {
+        let title = self.document.window_title();
+        if title == self.window_title {
+            return;
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = ctx;
+            if let Some(document) = web_sys::window().and_then(|w| w.document()) {
+                document.set_title(&title);
+            }
+        }
+
+        self.window_title = title;
+    }
+
+    fn show_document_menu(&mut self, ui: &mut egui::Ui) {
+        egui::MenuBar::new().ui(ui, |ui| {
+            ui.menu_button("File", |ui| {
+                if ui.button("New        Ctrl+N").clicked() {
+                    self.request_document_action(DocumentAction::New);
+                }
+                if ui.button("Open...    Ctrl+O").clicked() {
+                    self.request_document_action(DocumentAction::Open);
+                }
+
+                #[cfg(not(target_arch = "wasm32"))]
+                ui.menu_button("Open Recent", |ui| {
+                    if self.recent_files.paths().is_empty() {
+                        ui.label("No recent files");
+                    }
+                    for path in self.recent_files.paths().to_vec() {
+                        if ui.button(path.display().to_string()).clicked() {
+                            self.request_document_action(DocumentAction::OpenRecent(path));
+                        }
+                    }
+                });
+
+                ui.separator();
+
+                if ui.button("Save       Ctrl+S").clicked() {
+                    self.save_document();
+                }
+                if ui.button("Save As... Ctrl+Shift+S").clicked() {
+                    self.save_document_as();
+                }
+
+                ui.separator();
+
+                if ui
+                    .button("Import Events...")
+                    .on_hover_text("Add the events from another timeline file to this one")
+                    .clicked()
+                {
+                    self.import_dialog();
+                }
+            });
+
+            ui.menu_button("Edit", |ui| {
+                if ui
+                    .add_enabled(self.timeline.can_undo(), egui::Button::new("Undo  Ctrl+Z"))
+                    .clicked()
+                {
+                    self.undo();
+                }
+                if ui
+                    .add_enabled(
+                        self.timeline.can_redo(),
+                        egui::Button::new("Redo  Ctrl+Shift+Z"),
+                    )
+                    .clicked()
+                {
+                    self.redo();
+                }
+
+                ui.separator();
+
+                if ui.button("🎨 Categories...").clicked() {
+                    self.open_category_editor();
+                }
+                if ui.button("☰ Lanes...").clicked() {
+                    self.open_lane_editor();
+                }
+
+                let trash_label = format!("🗑 Trash ({})", self.timeline.trash().len());
+                if ui.button(trash_label).clicked() {
+                    self.show_trash = true;
+                }
+            });
+
+            ui.menu_button("View", |ui| self.show_view_menu(ui));
 
-#[cfg(not(target_arch = "wasm32"))]
-fn main() -> eframe::Result<()> {
-    let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([800.0, 600.0])
-            .with_title("Lifeline Timeline"),
-        ..Default::default()
-    };
+            ui.toggle_value(&mut self.show_search, "🔍 Search")
+                .on_hover_text("Ctrl+F");
+            if ui
+                .button("📅 Today")
+                .on_hover_text("Fly to today (T); ← and → step through events")
+                .clicked()
+            {
+                self.focus_today();
+            }
 
-    eframe::run_native(
-        "Lifeline",
-        options,
-        Box::new(|_cc| Ok(Box::new(LifelineApp::new()))),
-    )
-}
+            let marker = if self.document.is_dirty() { " *" } else { "" };
+            let metadata = self.timeline.metadata();
+            ui.label(
+                egui::RichText::new(format!("{}{}", self.document.name, marker))
+                    .color(Color32::from_gray(200)),
+            )
+            .on_hover_text(format!(
+                "Created {}\nModified {}",
+                format_calendar_day(metadata.created),
+                format_calendar_day(metadata.modified)
+            ));
+        });
+    }
 
-#[cfg(target_arch = "wasm32")]
-fn main() {
-    use wasm_bindgen::JsCast;
+    fn show_view_menu(&mut self, ui: &mut egui::Ui) {
+        if ui.button("Reset View").clicked() {
+            self.camera = Camera::new();
+            self.focused_event_id = None;
+        }
 
-    // Make sure panics are logged using console.error
-    console_error_panic_hook::set_once();
+        ui.separator();
+        let current = self.timeline.layout();
+        for mode in [LayoutMode::Cosmic, LayoutMode::Stable] {
+            if ui.radio(current == mode, mode.label()).clicked() && current != mode {
+                self.timeline.set_layout(mode);
+                // Positions frozen in one layout make no sense in the other
+                self.frozen_positions.clear();
+                self.resume_start_times.clear();
+                self.timeline_changed();
+            }
+        }
 
-    // Redirect tracing to console.log and friends
-    tracing_wasm::set_as_global_default();
+        ui.separator();
+        let current = self.settings.orientation;
+        for orientation in [Orientation::Horizontal, Orientation::Vertical] {
+            if ui
+                .radio(current == orientation, orientation.label())
+                .clicked()
+                && current != orientation
+            {
+                self.settings.orientation = orientation;
+                self.settings.save();
+                self.frozen_positions.clear();
+                self.resume_start_times.clear();
+                // The camera's axes swap, so the old view means nothing
+                self.camera = Camera::new();
+                self.focused_event_id = None;
+            }
+        }
 
-    let web_options = eframe::WebOptions::default();
+        if ui
+            .checkbox(&mut self.settings.show_minimap, "Overview Strip")
+            .changed()
+        {
+            self.settings.save();
+        }
 
-    wasm_bindgen_futures::spawn_local(async {
-        let document = web_sys::window()
-            .expect("No window")
-            .document()
-            .expect("No document");
+        ui.separator();
+        ui.label("Zoom limits");
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Out:");
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut self.settings.min_zoom)
+                        .range(settings::ZOOM_LIMIT_RANGE)
+                        .speed(0.01)
+                        .suffix("×"),
+                )
+                .changed();
+            ui.label("In:");
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut self.settings.max_zoom)
+                        .range(settings::ZOOM_LIMIT_RANGE)
+                        .speed(1.0)
+                        .suffix("×"),
+                )
+                .changed();
+        });
+        if let Some(layout) = self.timeline_layout() {
+            let finest = layout.seconds_per_point(self.settings.max_zoom);
+            ui.label(
+                egui::RichText::new(format!(
+                    "Zoomed in fully, one point of this timeline is {}.",
+                    event_renderer::format_duration(finest)
+                ))
+                .small()
+                .color(Color32::from_gray(160)),
+            );
+        }
+        if changed {
+            self.settings.max_zoom = self.settings.max_zoom.max(self.settings.min_zoom);
+            let zoom_range = self.settings.zoom_range();
+            self.camera.zoom = self
+                .camera
+                .zoom
+                .clamp(*zoom_range.start(), *zoom_range.end());
+            self.settings.save();
+        }
+    }
 
-        let canvas = document
-            .get_element_by_id("the_canvas_id")
-            .expect("Failed to find the_canvas_id")
-            .dyn_into::<web_sys::HtmlCanvasElement>()
-            .expect("the_canvas_id was not a HtmlCanvasElement");
+    fn handle_document_shortcuts(&mut self, ctx: &egui::Context) {
+        use egui::{Key, KeyboardShortcut, Modifiers};
 
-        let start_result = eframe::WebRunner::new()
-            .start(
-                canvas,
-                web_options,
-                Box::new(|_cc| Ok(Box::new(LifelineApp::new()))),
-            )
-            .await;
+        // Save As first: Ctrl+S would otherwise also match Ctrl+Shift+S
+        let save_as = KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::S);
+        let save = KeyboardShortcut::new(Modifiers::COMMAND, Key::S);
+        let open = KeyboardShortcut::new(Modifiers::COMMAND, Key::O);
+        let new = KeyboardShortcut::new(Modifiers::COMMAND, Key::N);
 
-        // Remove loading spinner
-        if let Some(loading) = document.get_element_by_id("loading") {
-            loading.remove();
+        if ctx.input_mut(|i| i.consume_shortcut(&save_as)) {
+            self.save_document_as();
+        } else if ctx.input_mut(|i| i.consume_shortcut(&save)) {
+            self.save_document();
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&open)) {
+            self.request_document_action(DocumentAction::Open);
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&new)) {
+            self.request_document_action(DocumentAction::New);
         }
 
-        start_result.expect("failed to start eframe");
-    });
-}
-
-struct LifelineApp {
-    stars: Vec<stars::Star>,
-    galaxies: Vec<stars::Galaxy>,
-    nebulas: Vec<stars::Nebula>,
-    start_time: Instant,
-    timeline: Timeline,
-    camera: Camera,
-    // Click-to-stop state
-    clicked_event_index: Option<usize>,
-    frozen_positions: HashMap<usize, (f32, f32)>,
-    resume_start_times: HashMap<usize, f32>,
-    // Deletion state
-    event_to_delete: Option<usize>,
-    // UI state for adding events
-    new_event_title: String,
-    new_event_description: String,
-    new_event_day: String,
-    new_event_month: String,
-    new_event_year: String,
-    new_event_image_path: String,
-    show_add_panel: bool,
-    // Image cache
-    image_cache: HashMap<String, egui::TextureHandle>,
-}
+        let find = KeyboardShortcut::new(Modifiers::COMMAND, Key::F);
+        if ctx.input_mut(|i| i.consume_shortcut(&find)) {
+            self.show_search = true;
+            ctx.memory_mut(|m| m.request_focus(egui::Id::new(SEARCH_FIELD_ID)));
+        }
 
-impl LifelineApp {
-    #[cfg(target_arch = "wasm32")]
-    fn save_to_storage(&self) {
-        if let Ok(json) = self.timeline.to_json() {
-            if let Some(window) = web_sys::window() {
-                if let Ok(Some(storage)) = window.local_storage() {
-                    let _ = storage.set_item("lifeline_events", &json);
-                }
-            }
+        // Text fields have their own undo
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        let redo = KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z);
+        let redo_alt = KeyboardShortcut::new(Modifiers::COMMAND, Key::Y);
+        let undo = KeyboardShortcut::new(Modifiers::COMMAND, Key::Z);
+
+        // Redo first, for the same reason as Save As
+        if ctx.input_mut(|i| i.consume_shortcut(&redo) || i.consume_shortcut(&redo_alt)) {
+            self.redo();
+        } else if ctx.input_mut(|i| i.consume_shortcut(&undo)) {
+            self.undo();
         }
     }
 
-    #[cfg(target_arch = "wasm32")]
-    fn load_from_storage() -> Timeline {
-        if let Some(window) = web_sys::window() {
-            if let Ok(Some(storage)) = window.local_storage() {
-                if let Ok(Some(json)) = storage.get_item("lifeline_events") {
-                    if let Ok(timeline) = Timeline::from_json(&json) {
-                        return timeline;
+    fn show_document_dialogs(&mut self, ctx: &egui::Context) {
+        if self.pending_discard.is_some() {
+            let mut choice = None;
+            egui::Window::new("Unsaved changes")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "\"{}\" has unsaved changes. Discard them?",
+                        self.document.name
+                    ));
+                    ui.add_space(8.0);
+                    ui.horizontal(|ui| {
+                        if ui.button("Discard changes").clicked() {
+                            choice = Some(true);
+                        }
+                        if ui.button("Cancel").clicked() {
+                            choice = Some(false);
+                        }
+                    });
+                });
+
+            match choice {
+                Some(true) => {
+                    if let Some(action) = self.pending_discard.take() {
+                        self.perform_document_action(action);
                     }
                 }
+                Some(false) => self.pending_discard = None,
+                None => {}
             }
         }
-        Timeline::new()
-    }
-
-    fn new() -> Self {
-        // Generate background cosmic objects
-        let stars = stars::generate_stars(150);
-        let galaxies = stars::generate_galaxies(4);
-        let nebulas = stars::generate_nebulas(3);
 
-        // Create timeline - load from storage in WASM, or create new
-        #[cfg(target_arch = "wasm32")]
-        let timeline = Self::load_from_storage();
-
-        #[cfg(not(target_arch = "wasm32"))]
-        let timeline = Timeline::new();
-
-        Self {
-            stars,
-            galaxies,
-            nebulas,
-            start_time: Instant::now(),
-            timeline,
-            camera: Camera {
-                offset_x: 0.0,
-                offset_y: 0.0,
-                zoom: 1.0,
-            },
-            clicked_event_index: None,
-            frozen_positions: HashMap::new(),
-            resume_start_times: HashMap::new(),
-            event_to_delete: None,
-            new_event_title: String::new(),
-            new_event_description: String::new(),
-            new_event_day: String::new(),
-            new_event_month: String::new(),
-            new_event_year: String::new(),
-            new_event_image_path: String::new(),
-            show_add_panel: false,
-            image_cache: HashMap::new(),
+        if let Some(message) = self.document_error.clone() {
+            egui::Window::new("Document error")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(message);
+                    ui.add_space(8.0);
+                    if ui.button("OK").clicked() {
+                        self.document_error = None;
+                    }
+                });
         }
     }
 
@@ -186,7 +1687,10 @@ impl LifelineApp {
                     }
                 }
                 #[cfg(not(target_arch = "wasm32"))]
-                None
+                {
+                    let _ = base64_data;
+                    None
+                }
             } else {
                 None
             }
@@ -210,6 +1714,68 @@ impl LifelineApp {
             None
         }
     }
+
+    fn add_event_from_form(&mut self) {
+        let mut event = match self.event_form.validate() {
+            Ok(event) => event,
+            Err(errors) => {
+                self.form_errors = errors;
+                return;
+            }
+        };
+
+        let golden_yellow = Color32::from_rgb(255, 215, 0);
+        event.color = golden_yellow;
+        self.timeline.add_event(event);
+
+        self.timeline_changed();
+
+        self.event_form = EventInput::default();
+        self.form_errors.clear();
+    }
+
+    fn add_today_event_from_form(&mut self) {
+        self.event_form.set_date(Date::today());
+        self.add_event_from_form();
+    }
+
+    fn start_editing(&mut self, id: EventId) {
+        if let Some(event) = self.timeline.event(id) {
+            self.event_form = EventInput::from_event(event);
+            self.form_errors.clear();
+            self.editing_event_id = Some(id);
+            self.show_add_panel = true;
+        }
+    }
+
+    fn save_edited_event(&mut self) {
+        let Some(id) = self.editing_event_id else {
+            return;
+        };
+        let mut event = match self.event_form.validate() {
+            Ok(event) => event,
+            Err(errors) => {
+                self.form_errors = errors;
+                return;
+            }
+        };
+
+        if let Some(original) = self.timeline.event(id) {
+            event.color = original.color;
+        }
+        self.timeline.update_event(id, event);
+
+        self.timeline_changed();
+
+        self.stop_editing();
+    }
+
+    fn stop_editing(&mut self) {
+        self.editing_event_id = None;
+        self.event_form = EventInput::default();
+        self.form_errors.clear();
+        self.show_add_panel = false;
+    }
 }
 
 impl eframe::App for LifelineApp {
@@ -220,46 +1786,122 @@ impl eframe::App for LifelineApp {
             if let Some(window) = web_sys::window() {
                 if let Ok(Some(storage)) = window.local_storage() {
                     if let Ok(Some(data_url)) = storage.get_item("lifeline_temp_image") {
-                        self.new_event_image_path = data_url;
+                        self.event_form.image_path = data_url;
                         let _ = storage.remove_item("lifeline_temp_image");
                     }
                 }
             }
         }
 
+        // Check for files picked through the browser file picker (Open or Import)
+        #[cfg(target_arch = "wasm32")]
+        self.receive_picked_files();
+
+        self.handle_document_shortcuts(ctx);
+
+        egui::TopBottomPanel::top("document_menu")
+            .show_separator_line(false)
+            .frame(
+                egui::Frame::new()
+                    .fill(Color32::from_rgba_unmultiplied(0, 0, 0, 200))
+                    .inner_margin(4.0),
+            )
+            .show(ctx, |ui| self.show_document_menu(ui));
+
+        self.show_search_panel(ctx);
+
         // Handle camera input - but only if add panel is hidden and no text edit is focused
         let pan_speed = 5.0;
         let zoom_speed = 0.1;
+        let projection = self.projection();
 
         let wants_keyboard_input = ctx.wants_keyboard_input();
 
         if !self.show_add_panel && !wants_keyboard_input {
             ctx.input(|i| {
+                // Leave Ctrl/Cmd+key combinations to the shortcuts
+                if i.modifiers.command {
+                    return;
+                }
+
                 // WASD for panning
+                let panning = [egui::Key::W, egui::Key::A, egui::Key::S, egui::Key::D]
+                    .iter()
+                    .any(|&key| i.key_down(key));
+                if panning {
+                    self.camera.stop_flight();
+                    self.focused_event_id = None;
+                }
+                // On screen, so W still moves the view up when time runs downwards
+                let mut delta = egui::Vec2::ZERO;
                 if i.key_down(egui::Key::W) {
-                    self.camera.offset_y += pan_speed;
+                    delta.y += pan_speed;
                 }
                 if i.key_down(egui::Key::S) {
-                    self.camera.offset_y -= pan_speed;
+                    delta.y -= pan_speed;
                 }
                 if i.key_down(egui::Key::A) {
-                    self.camera.offset_x += pan_speed;
+                    delta.x += pan_speed;
                 }
                 if i.key_down(egui::Key::D) {
-                    self.camera.offset_x -= pan_speed;
+                    delta.x -= pan_speed;
                 }
+                self.camera.pan(projection.vec_to_layout(delta));
+            });
+
+            // Arrow keys along the timeline step through events, T flies to today
+            let (later, earlier) = match self.settings.orientation {
+                Orientation::Horizontal => (egui::Key::ArrowRight, egui::Key::ArrowLeft),
+                Orientation::Vertical => (egui::Key::ArrowDown, egui::Key::ArrowUp),
+            };
+            let (next, previous, today) = ctx.input(|i| {
+                (
+                    i.key_pressed(later),
+                    i.key_pressed(earlier),
+                    !i.modifiers.command && i.key_pressed(egui::Key::T),
+                )
             });
+            if next || previous {
+                self.focus_adjacent_event(next);
+            }
+            if today {
+                self.focus_today();
+            }
         }
 
-        // Scroll wheel for zooming - always available
+        // Zooming around the cursor with the wheel, or around the fingers when pinching
+        let zoom_range = self.settings.zoom_range();
+        let timeline_rect = self.timeline_rect;
         ctx.input(|i| {
+            if let Some(touch) = i.multi_touch() {
+                self.camera.stop_flight();
+                self.focused_event_id = None;
+                self.camera.zoom_at(
+                    touch.zoom_delta,
+                    projection.to_layout(touch.center_pos),
+                    zoom_range.clone(),
+                );
+                self.camera
+                    .pan(projection.vec_to_layout(touch.translation_delta));
+                return;
+            }
+
+            // Only over the timeline, so scrolling a side panel's list doesn't zoom
+            let Some(pointer) = i.pointer.hover_pos().filter(|p| timeline_rect.contains(*p)) else {
+                return;
+            };
             let scroll_delta = i.smooth_scroll_delta.y;
-            if scroll_delta != 0.0 {
-                self.camera.zoom *= 1.0 + scroll_delta * zoom_speed * 0.01;
-                self.camera.zoom = self.camera.zoom.clamp(0.1, 5.0);
+            // Trackpad pinch and Ctrl+wheel arrive as a zoom delta rather than a scroll
+            let factor = (1.0 + scroll_delta * zoom_speed * 0.01) * i.zoom_delta();
+            if factor != 1.0 {
+                self.camera.stop_flight();
+                self.camera
+                    .zoom_at(factor, projection.to_layout(pointer), zoom_range.clone());
             }
         });
 
+        self.camera.update(self.start_time.elapsed().as_secs_f32());
+
         egui::CentralPanel::default()
             .frame(egui::Frame::new().fill(egui::Color32::BLACK))
             .show(ctx, |ui| {
@@ -268,6 +1910,10 @@ impl eframe::App for LifelineApp {
                 // Draw background cosmic objects with parallax (back to front)
                 let painter = ui.painter();
                 let rect = ui.available_rect_before_wrap();
+                // The sky scrolls the same way on screen as the timeline
+                let sky_offset = self
+                    .projection()
+                    .vec_to_screen(egui::vec2(self.camera.offset_x, self.camera.offset_y));
 
                 // Render nebulas first (furthest back)
                 stars::render_nebulas(
@@ -275,8 +1921,8 @@ impl eframe::App for LifelineApp {
                     painter,
                     rect,
                     time,
-                    self.camera.offset_x,
-                    self.camera.offset_y,
+                    sky_offset.x,
+                    sky_offset.y,
                     self.camera.zoom,
                 );
 
@@ -285,9 +1931,8 @@ impl eframe::App for LifelineApp {
                     &self.galaxies,
                     painter,
                     rect,
-                    time,
-                    self.camera.offset_x,
-                    self.camera.offset_y,
+                    sky_offset.x,
+                    sky_offset.y,
                     self.camera.zoom,
                 );
 
@@ -297,8 +1942,8 @@ impl eframe::App for LifelineApp {
                     painter,
                     rect,
                     time,
-                    self.camera.offset_x,
-                    self.camera.offset_y,
+                    sky_offset.x,
+                    sky_offset.y,
                     self.camera.zoom,
                 );
 
@@ -316,6 +1961,7 @@ impl eframe::App for LifelineApp {
                 }
 
                 // Draw timeline events (returns interaction info)
+                self.timeline_rect = ui.available_rect_before_wrap();
                 let interaction = event_renderer::render_timeline_events(
                     &self.timeline,
                     time,
@@ -323,31 +1969,98 @@ impl eframe::App for LifelineApp {
                     &self.camera,
                     &mut self.frozen_positions,
                     &mut self.resume_start_times,
-                    self.clicked_event_index,
+                    self.clicked_event_id,
                     &self.image_cache,
+                    (self.show_search && self.filter.is_active()).then_some(&self.filter),
+                    self.settings.max_zoom,
+                    self.settings.orientation,
+                    self.settings.show_minimap,
                 );
 
                 // Update clicked state
-                self.clicked_event_index = interaction.clicked_index;
+                self.clicked_event_id = interaction.clicked_id;
+
+                if let Some(id) = interaction.toggle_lane {
+                    let collapsed = self.timeline.lane(id).is_some_and(|l| l.collapsed);
+                    self.timeline.set_lane_collapsed(id, !collapsed);
+                    self.timeline_changed();
+                }
+
+                if let Some((point, factor)) = interaction.zoom_to {
+                    let zoom_range = self.settings.zoom_range();
+                    self.camera.fly_to(
+                        point,
+                        self.view_center(),
+                        (self.camera.zoom * factor).clamp(*zoom_range.start(), *zoom_range.end()),
+                        time,
+                    );
+                    self.focused_event_id = None;
+                }
 
                 // Handle deletion request
-                if let Some(index) = interaction.delete_index {
-                    self.event_to_delete = Some(index);
+                if let Some(id) = interaction.delete_id {
+                    if self.settings.confirm_delete {
+                        self.pending_delete = Some(id);
+                    } else {
+                        self.event_to_delete = Some(id);
+                    }
+                }
+
+                if let Some(id) = interaction.edit_id {
+                    self.start_editing(id);
+                }
+
+                // Dragging empty space pans; dragging that starts on an event just holds it
+                let drag = ui.interact(
+                    self.timeline_rect,
+                    ui.id().with("timeline_drag"),
+                    egui::Sense::drag(),
+                );
+                if drag.drag_started() {
+                    self.drag_panning = interaction.clicked_id.is_none()
+                        || drag.dragged_by(egui::PointerButton::Middle);
+                }
+                let touching = ui.input(|i| i.multi_touch().is_some());
+                if self.drag_panning && drag.dragged() && !touching {
+                    self.camera.stop_flight();
+                    self.focused_event_id = None;
+                    self.camera
+                        .pan(self.projection().vec_to_layout(drag.drag_delta()));
+                }
+                if drag.drag_stopped() {
+                    self.drag_panning = false;
+                }
+
+                if self.settings.show_minimap
+                    && minimap::show_minimap(
+                        ui,
+                        self.timeline_rect,
+                        &self.timeline,
+                        &mut self.camera,
+                        (self.show_search && self.filter.is_active()).then_some(&self.filter),
+                        self.settings.orientation,
+                    )
+                {
+                    self.camera.stop_flight();
+                    self.focused_event_id = None;
                 }
             });
 
         // Process deletion outside of central panel
-        if let Some(index) = self.event_to_delete.take() {
-            self.timeline.remove_event(index);
-
-            // Clear frozen positions and clicked state
-            self.frozen_positions.clear();
-            self.resume_start_times.clear();
-            self.clicked_event_index = None;
+        if let Some(id) = self.event_to_delete.take() {
+            self.timeline.remove_event(id);
+
+            // Only the deleted event's interaction state goes away; the rest is keyed by ID
+            self.frozen_positions.remove(&id);
+            self.resume_start_times.remove(&id);
+            if self.clicked_event_id == Some(id) {
+                self.clicked_event_id = None;
+            }
+            if self.editing_event_id == Some(id) {
+                self.stop_editing();
+            }
 
-            // Save to storage in WASM
-            #[cfg(target_arch = "wasm32")]
-            self.save_to_storage();
+            self.timeline_changed();
         }
 
         // Bottom panel for adding events (centered)
@@ -367,7 +2080,20 @@ impl eframe::App for LifelineApp {
                         .add_sized([150.0, 40.0], egui::Button::new(button_text))
                         .clicked()
                     {
-                        self.show_add_panel = !self.show_add_panel;
+                        if self.editing_event_id.is_some() {
+                            self.stop_editing();
+                        } else {
+                            self.show_add_panel = !self.show_add_panel;
+                        }
+                    }
+
+                    if self.show_add_panel && self.editing_event_id.is_some() {
+                        ui.add_space(5.0);
+                        ui.label(
+                            egui::RichText::new("Editing event")
+                                .color(Color32::from_rgb(255, 215, 0))
+                                .strong(),
+                        );
                     }
 
                     if self.show_add_panel {
@@ -376,10 +2102,10 @@ impl eframe::App for LifelineApp {
                         ui.vertical(|ui| {
                             ui.horizontal(|ui| {
                                 ui.label("Title:");
-                                ui.text_edit_singleline(&mut self.new_event_title);
+                                ui.text_edit_singleline(&mut self.event_form.title);
 
                                 ui.label("Description:");
-                                ui.text_edit_singleline(&mut self.new_event_description);
+                                ui.text_edit_singleline(&mut self.event_form.description);
                             });
 
                             ui.add_space(5.0);
@@ -387,28 +2113,146 @@ impl eframe::App for LifelineApp {
                             ui.horizontal(|ui| {
                                 ui.label("Day:");
                                 ui.add(
-                                    egui::TextEdit::singleline(&mut self.new_event_day)
+                                    egui::TextEdit::singleline(&mut self.event_form.day)
+                                        .hint_text("opt.")
+                                        .desired_width(40.0),
+                                );
+
+                                ui.label("Month:");
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut self.event_form.month)
+                                        .hint_text("1-12 / spring")
+                                        .desired_width(80.0),
+                                );
+
+                                ui.label("Year:");
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut self.event_form.year)
+                                        .desired_width(80.0),
+                                );
+
+                                ui.checkbox(&mut self.event_form.circa, "circa");
+
+                                ui.label("Time:");
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut self.event_form.time)
+                                        .hint_text("HH:MM")
+                                        .desired_width(60.0),
+                                );
+
+                                ui.label("Zone:");
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut self.event_form.timezone)
+                                        .hint_text("UTC / +02:00 / Europe/Oslo")
+                                        .desired_width(120.0),
+                                );
+                            });
+
+                            ui.add_space(5.0);
+
+                            ui.horizontal(|ui| {
+                                ui.label("Ends (optional) Day:");
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut self.event_form.end_day)
                                         .desired_width(40.0),
                                 );
 
                                 ui.label("Month:");
                                 ui.add(
-                                    egui::TextEdit::singleline(&mut self.new_event_month)
+                                    egui::TextEdit::singleline(&mut self.event_form.end_month)
                                         .desired_width(40.0),
                                 );
 
                                 ui.label("Year:");
                                 ui.add(
-                                    egui::TextEdit::singleline(&mut self.new_event_year)
+                                    egui::TextEdit::singleline(&mut self.event_form.end_year)
                                         .desired_width(80.0),
                                 );
                             });
 
                             ui.add_space(5.0);
 
+                            ui.horizontal(|ui| {
+                                ui.label("Category:");
+                                let categories = self.timeline.categories();
+                                let selected = self
+                                    .event_form
+                                    .category
+                                    .and_then(|id| self.timeline.category(id))
+                                    .map_or("None".to_string(), |c| c.name.clone());
+                                egui::ComboBox::from_id_salt("event_category")
+                                    .selected_text(selected)
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(
+                                            &mut self.event_form.category,
+                                            None,
+                                            "None",
+                                        );
+                                        for category in categories {
+                                            ui.selectable_value(
+                                                &mut self.event_form.category,
+                                                Some(category.id),
+                                                egui::RichText::new(format!("● {}", category.name))
+                                                    .color(category.color32()),
+                                            );
+                                        }
+                                    });
+                                if ui
+                                    .small_button("🎨")
+                                    .on_hover_text("Edit categories")
+                                    .clicked()
+                                {
+                                    self.open_category_editor();
+                                }
+
+                                ui.label("Tags:");
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut self.event_form.tags)
+                                        .hint_text("comma, separated")
+                                        .desired_width(200.0),
+                                );
+
+                                ui.label("Lane:");
+                                let lanes = self.timeline.lanes();
+                                let selected = self
+                                    .event_form
+                                    .lane
+                                    .and_then(|id| self.timeline.lane(id))
+                                    .map_or("None".to_string(), |l| l.name.clone());
+                                egui::ComboBox::from_id_salt("event_lane")
+                                    .selected_text(selected)
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(
+                                            &mut self.event_form.lane,
+                                            None,
+                                            "None",
+                                        );
+                                        for lane in lanes {
+                                            ui.selectable_value(
+                                                &mut self.event_form.lane,
+                                                Some(lane.id),
+                                                &lane.name,
+                                            );
+                                        }
+                                    });
+                                if ui.small_button("☰").on_hover_text("Edit lanes").clicked() {
+                                    self.open_lane_editor();
+                                }
+                            });
+
+                            // Inline validation errors from the last submit attempt
+                            for error in &self.form_errors {
+                                ui.label(
+                                    egui::RichText::new(format!("⚠ {}", error))
+                                        .color(Color32::from_rgb(255, 110, 110)),
+                                );
+                            }
+
+                            ui.add_space(5.0);
+
                             ui.horizontal(|ui| {
                                 ui.label("Image Path:");
-                                ui.text_edit_singleline(&mut self.new_event_image_path);
+                                ui.text_edit_singleline(&mut self.event_form.image_path);
 
                                 #[cfg(not(target_arch = "wasm32"))]
                                 if ui.button("Browse...").clicked() {
@@ -419,7 +2263,7 @@ impl eframe::App for LifelineApp {
                                         )
                                         .pick_file()
                                     {
-                                        self.new_event_image_path = path.display().to_string();
+                                        self.event_form.image_path = path.display().to_string();
                                     }
                                 }
 
@@ -456,76 +2300,37 @@ impl eframe::App for LifelineApp {
                                     });
                                 }
 
-                                if ui
-                                    .add_sized([120.0, 25.0], egui::Button::new("Add to Timeline"))
-                                    .clicked()
-                                {
-                                    let day = self.new_event_day.parse::<u8>().unwrap_or(1);
-                                    let month = self.new_event_month.parse::<u8>().unwrap_or(1);
-                                    let year = self.new_event_year.parse::<i32>().unwrap_or(2026);
-
-                                    let image_path = if self.new_event_image_path.is_empty() {
-                                        None
-                                    } else {
-                                        Some(self.new_event_image_path.clone())
-                                    };
-
-                                    let golden_yellow = Color32::from_rgb(255, 215, 0);
-                                    let mut event = Event::new(
-                                        self.new_event_title.clone(),
-                                        self.new_event_description.clone(),
-                                        day,
-                                        month,
-                                        year,
-                                        image_path,
-                                    );
-                                    event.color = golden_yellow;
-                                    self.timeline.add_event(event);
-
-                                    // Save to storage in WASM
-                                    #[cfg(target_arch = "wasm32")]
-                                    self.save_to_storage();
-
-                                    self.new_event_title.clear();
-                                    self.new_event_description.clear();
-                                    self.new_event_day.clear();
-                                    self.new_event_month.clear();
-                                    self.new_event_year.clear();
-                                    self.new_event_image_path.clear();
-                                }
+                                if self.editing_event_id.is_some() {
+                                    if ui
+                                        .add_sized([120.0, 25.0], egui::Button::new("Save Changes"))
+                                        .clicked()
+                                    {
+                                        self.save_edited_event();
+                                    }
 
-                                if ui
-                                    .add_sized([80.0, 25.0], egui::Button::new("Today"))
-                                    .clicked()
-                                {
-                                    let image_path = if self.new_event_image_path.is_empty() {
-                                        None
-                                    } else {
-                                        Some(self.new_event_image_path.clone())
-                                    };
-
-                                    let golden_yellow = Color32::from_rgb(255, 215, 0);
-                                    let mut event = Event::new(
-                                        self.new_event_title.clone(),
-                                        self.new_event_description.clone(),
-                                        16,
-                                        1,
-                                        2026,
-                                        image_path,
-                                    );
-                                    event.color = golden_yellow;
-                                    self.timeline.add_event(event);
-
-                                    // Save to storage in WASM
-                                    #[cfg(target_arch = "wasm32")]
-                                    self.save_to_storage();
-
-                                    self.new_event_title.clear();
-                                    self.new_event_description.clear();
-                                    self.new_event_day.clear();
-                                    self.new_event_month.clear();
-                                    self.new_event_year.clear();
-                                    self.new_event_image_path.clear();
+                                    if ui
+                                        .add_sized([80.0, 25.0], egui::Button::new("Cancel"))
+                                        .clicked()
+                                    {
+                                        self.stop_editing();
+                                    }
+                                } else {
+                                    if ui
+                                        .add_sized(
+                                            [120.0, 25.0],
+                                            egui::Button::new("Add to Timeline"),
+                                        )
+                                        .clicked()
+                                    {
+                                        self.add_event_from_form();
+                                    }
+
+                                    if ui
+                                        .add_sized([80.0, 25.0], egui::Button::new("Today"))
+                                        .clicked()
+                                    {
+                                        self.add_today_event_from_form();
+                                    }
                                 }
                             });
                         });
@@ -535,6 +2340,14 @@ impl eframe::App for LifelineApp {
                 });
             });
 
+        self.show_document_dialogs(ctx);
+        self.show_recovery_dialog(ctx);
+        self.show_delete_confirmation(ctx);
+        self.show_trash_window(ctx);
+        self.show_category_editor(ctx);
+        self.show_lane_editor(ctx);
+        self.update_window_title(ctx);
+
         // Request continuous repaint for animation
         ctx.request_repaint();
     }
@@ -0,0 +1,129 @@
+//! Upgrades saved timelines from older format versions.
+//!
+//! Version 1 was a bare JSON array of events. Version 2 wraps the events in an envelope
+//! with a `version` field and timeline metadata. Version 3 adds the trash, version 4 the
+//! category palette, version 5 the layout mode and version 6 the lane list. To change the
+//! format, bump [`CURRENT_VERSION`] and append a step to [`MIGRATIONS`].
+
+use serde::de::Error as _;
+use serde_json::{json, Value};
+
+pub const CURRENT_VERSION: u64 = 6;
+
+/// `MIGRATIONS[n]` turns a version `n + 1` document into a version `n + 2` one.
+const MIGRATIONS: [fn(Value, i64) -> Value; (CURRENT_VERSION - 1) as usize] =
+    [v1_to_v2, v2_to_v3, v3_to_v4, v4_to_v5, v5_to_v6];
+
+/// Format version of a parsed document, or `None` if it isn't a timeline at all.
+pub fn version_of(value: &Value) -> Option<u64> {
+    match value {
+        Value::Array(_) => Some(1),
+        Value::Object(map) => map.get("version")?.as_u64(),
+        _ => None,
+    }
+}
+
+/// Runs every migration needed to bring `value` up to [`CURRENT_VERSION`].
+/// `now` (seconds since the Unix epoch) fills in metadata older versions didn't record.
+pub fn upgrade(mut value: Value, now: i64) -> Result<Value, serde_json::Error> {
+    let version = version_of(&value)
+        .ok_or_else(|| serde_json::Error::custom("expected a timeline document"))?;
+    if version == 0 || version > CURRENT_VERSION {
+        return Err(serde_json::Error::custom(format!(
+            "unsupported format version {} (this build reads up to {})",
+            version, CURRENT_VERSION
+        )));
+    }
+
+    for migrate in &MIGRATIONS[(version - 1) as usize..] {
+        value = migrate(value, now);
+    }
+    Ok(value)
+}
+
+/// Bare event array → envelope. Creation time wasn't recorded, so it starts now.
+fn v1_to_v2(events: Value, now: i64) -> Value {
+    json!({
+        "version": 2,
+        "metadata": {
+            "name": crate::document::UNTITLED,
+            "created": now,
+            "modified": now,
+        },
+        "events": events,
+    })
+}
+
+/// Adds an empty trash.
+fn v2_to_v3(mut document: Value, _now: i64) -> Value {
+    document["version"] = json!(3);
+    document["trash"] = json!([]);
+    document
+}
+
+/// Adds the default category palette.
+fn v3_to_v4(mut document: Value, _now: i64) -> Value {
+    document["version"] = json!(4);
+    document["categories"] = json!(crate::category::default_palette());
+    document
+}
+
+/// Adds the layout mode, keeping the drifting layout older versions always used.
+fn v4_to_v5(mut document: Value, _now: i64) -> Value {
+    document["version"] = json!(5);
+    document["layout"] = json!(crate::layout::LayoutMode::Cosmic);
+    document
+}
+
+/// Adds an empty lane list, so every event shares the one band as before.
+fn v5_to_v6(mut document: Value, _now: i64) -> Value {
+    document["version"] = json!(6);
+    document["lanes"] = json!([]);
+    document
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upgrades_a_bare_event_list() {
+        let events = json!([{ "title": "Old event" }]);
+        assert_eq!(version_of(&events), Some(1));
+
+        let upgraded = upgrade(events.clone(), 1_700_000_000).unwrap();
+        assert_eq!(version_of(&upgraded), Some(CURRENT_VERSION));
+        assert_eq!(upgraded["events"], events);
+        assert_eq!(upgraded["metadata"]["created"], 1_700_000_000);
+        assert_eq!(upgraded["trash"], json!([]));
+    }
+
+    #[test]
+    fn current_documents_are_left_alone() {
+        let document = json!({ "version": CURRENT_VERSION, "events": [] });
+        assert_eq!(upgrade(document.clone(), 0).unwrap(), document);
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        assert!(upgrade(json!({ "version": 0 }), 0).is_err());
+        assert!(upgrade(json!({ "version": CURRENT_VERSION + 1 }), 0).is_err());
+        assert!(upgrade(json!({ "events": [] }), 0).is_err());
+        assert!(upgrade(json!("events"), 0).is_err());
+    }
+
+    #[test]
+    fn older_documents_keep_the_cosmic_layout() {
+        let document = json!({ "version": 4, "events": [], "trash": [], "categories": [] });
+        let upgraded = upgrade(document, 0).unwrap();
+        assert_eq!(upgraded["layout"], json!(crate::layout::LayoutMode::Cosmic));
+    }
+
+    #[test]
+    fn older_documents_get_an_empty_lane_list() {
+        let document = json!({ "version": 5, "events": [], "layout": "stable" });
+        let upgraded = upgrade(document, 0).unwrap();
+        assert_eq!(upgraded["lanes"], json!([]));
+        assert_eq!(upgraded["layout"], "stable");
+    }
+}
@@ -0,0 +1,127 @@
+//! The overview strip along the top (or, when vertical, the left) of the timeline, showing
+//! every event across the full date range and where the view currently is.
+
+use crate::event_renderer::{Camera, TimelineLayout};
+use crate::filter::{EventFilter, FilterDisplay};
+use crate::layout::Orientation;
+use crate::timeline::Timeline;
+use eframe::egui;
+use eframe::epaint::{Color32, Pos2, Rect, Stroke};
+
+const HEIGHT: f32 = 24.0;
+
+/// The strip in layout space.
+fn strip_rect(layout: &TimelineLayout) -> Rect {
+    Rect::from_min_size(
+        Pos2::new(layout.start_x, layout.rect.top() + 8.0),
+        egui::vec2(layout.width, HEIGHT),
+    )
+}
+
+/// Where the strip takes the pointer on screen, so the events beneath it don't.
+pub fn hit_rect(layout: &TimelineLayout) -> Rect {
+    layout
+        .projection
+        .rect_to_screen(strip_rect(layout).expand(4.0))
+}
+
+/// Draws the overview strip at the start of `screen_rect` (the timeline area). Clicking it
+/// centres the view on that point, and dragging moves the view along with the pointer.
+/// Returns whether the view was moved.
+pub fn show_minimap(
+    ui: &mut egui::Ui,
+    screen_rect: Rect,
+    timeline: &Timeline,
+    camera: &mut Camera,
+    filter: Option<&EventFilter>,
+    orientation: Orientation,
+) -> bool {
+    let Some(layout) = TimelineLayout::new(timeline, screen_rect, orientation) else {
+        return false;
+    };
+    // Laid out as if horizontal, like the timeline itself
+    let (rect, projection) = (layout.rect, layout.projection);
+    let strip = strip_rect(&layout);
+    // The strip shows the resting timeline, before the camera transform, squeezed to fit
+    let to_strip = |x: f32| strip.left() + (x - layout.start_x) / layout.width * strip.width();
+    let from_strip = |x: f32| layout.start_x + (x - strip.left()) / strip.width() * layout.width;
+
+    let view_left = to_strip((rect.left() - camera.offset_x) / camera.zoom);
+    let view_right = to_strip((rect.right() - camera.offset_x) / camera.zoom);
+    let viewport = Rect::from_x_y_ranges(view_left..=view_right, strip.y_range());
+
+    let response = ui.interact(
+        hit_rect(&layout),
+        ui.id().with("minimap"),
+        egui::Sense::click_and_drag(),
+    );
+    let mut moved = false;
+    if let Some(pointer) = response.interact_pointer_pos() {
+        let pointer = projection.to_layout(pointer);
+        // Grabbing the viewport keeps the grab point under the pointer; anywhere else
+        // jumps the view there first
+        let grab_id = ui.id().with("minimap_grab");
+        if ui.input(|i| i.pointer.any_pressed()) {
+            let grab = if viewport.x_range().contains(pointer.x) {
+                pointer.x - viewport.center().x
+            } else {
+                0.0
+            };
+            ui.data_mut(|d| d.insert_temp(grab_id, grab));
+        }
+        let grab: f32 = ui.data(|d| d.get_temp(grab_id)).unwrap_or(0.0);
+        let target = from_strip(pointer.x - grab);
+        camera.offset_x = rect.center().x - target * camera.zoom;
+        moved = true;
+    }
+
+    let painter = ui
+        .painter()
+        .with_clip_rect(projection.rect_to_screen(strip.expand(1.0)));
+    painter.rect_filled(
+        projection.rect_to_screen(strip),
+        4.0,
+        Color32::from_black_alpha(140),
+    );
+    let at = |x: f32, y: f32| projection.to_screen(Pos2::new(x, y));
+
+    let y = strip.center().y;
+    for event in timeline.events() {
+        let matches = filter.is_none_or(|f| f.matches(timeline, event));
+        if !matches && filter.is_some_and(|f| f.display == FilterDisplay::Hide) {
+            continue;
+        }
+        let mut tint = timeline.event_color(event);
+        if !matches {
+            tint = tint.gamma_multiply(0.2);
+        }
+        let start = to_strip(layout.x(event.timestamp));
+        if event.is_span() {
+            let end = to_strip(layout.x(event.last_timestamp()));
+            painter.line_segment([at(start, y), at(end, y)], Stroke::new(3.0, tint));
+        } else {
+            painter.circle_filled(at(start, y), 2.0, tint);
+        }
+    }
+
+    painter.rect(
+        projection.rect_to_screen(viewport),
+        3.0,
+        Color32::from_white_alpha(if response.hovered() { 30 } else { 18 }),
+        Stroke::new(1.0, Color32::from_white_alpha(140)),
+        egui::StrokeKind::Inside,
+    );
+    painter.rect_stroke(
+        projection.rect_to_screen(strip),
+        4.0,
+        Stroke::new(1.0, Color32::from_white_alpha(40)),
+        egui::StrokeKind::Inside,
+    );
+
+    if response.dragged() {
+        ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+    } else if response.hovered() {
+        ui.ctx().set_cursor_icon(egui::CursorIcon::Grab);
+    }
+    moved
+}
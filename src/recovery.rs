@@ -0,0 +1,175 @@
+//! Handling for saved timelines that no longer parse, so they are never silently replaced
+//! by an empty one.
+
+use serde_json::error::Category;
+
+/// Saved data that failed to load, kept so the user can decide what to do with it.
+pub struct CorruptData {
+    /// Bytes that aren't UTF-8 are replaced with U+FFFD; the backup keeps the originals
+    pub raw: String,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    category: Category,
+    /// Whether a copy of the original data was written somewhere the next save won't touch
+    pub backed_up: bool,
+}
+
+impl CorruptData {
+    pub fn new(raw: String, err: &serde_json::Error) -> Self {
+        // serde_json appends the position to its message; it is shown separately
+        let message = err.to_string();
+        let position = format!(" at line {} column {}", err.line(), err.column());
+        let message = message
+            .strip_suffix(&position)
+            .unwrap_or(&message)
+            .to_string();
+
+        Self {
+            raw,
+            message,
+            line: err.line(),
+            column: err.column(),
+            category: err.classify(),
+            backed_up: false,
+        }
+    }
+
+    /// Data that isn't valid UTF-8, positioned at the first byte that isn't.
+    pub fn invalid_utf8(bytes: &[u8], err: std::str::Utf8Error) -> Self {
+        let valid = String::from_utf8_lossy(&bytes[..err.valid_up_to()]);
+        let line_start = valid.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            raw: String::from_utf8_lossy(bytes).into_owned(),
+            message: "invalid UTF-8".to_string(),
+            line: valid.matches('\n').count() + 1,
+            column: valid[line_start..].chars().count() + 1,
+            category: Category::Syntax,
+            backed_up: false,
+        }
+    }
+
+    /// A file that exists but couldn't be read at all.
+    pub fn unreadable(err: &std::io::Error) -> Self {
+        Self {
+            raw: String::new(),
+            message: err.to_string(),
+            line: 0,
+            column: 0,
+            category: Category::Io,
+            backed_up: false,
+        }
+    }
+
+    pub fn explanation(&self) -> &'static str {
+        match self.category {
+            Category::Eof => {
+                "The saved data ends unexpectedly. It was probably cut off while being written."
+            }
+            Category::Syntax => "The saved data is not valid JSON.",
+            Category::Data => {
+                "The saved data is valid JSON, but doesn't match the timeline format."
+            }
+            Category::Io => "The saved data could not be read.",
+        }
+    }
+
+    /// The line the error is on, shortened to a window around the error column.
+    pub fn excerpt(&self) -> Option<String> {
+        const CONTEXT: usize = 40;
+
+        let line = self.raw.lines().nth(self.line.checked_sub(1)?)?;
+        let chars: Vec<char> = line.chars().collect();
+        let column = self.column.min(chars.len());
+        let start = column.saturating_sub(CONTEXT);
+        let end = (column + CONTEXT).min(chars.len());
+
+        let mut excerpt: String = chars[start..end].iter().collect();
+        if start > 0 {
+            excerpt.insert(0, '…');
+        }
+        if end < chars.len() {
+            excerpt.push('…');
+        }
+        Some(excerpt)
+    }
+}
+
+/// Finds the text of each object in the event list of a damaged document, without
+/// requiring the document as a whole to parse. An object cut off by the end of the text is
+/// included as-is (and will fail to parse on its own).
+pub fn event_objects(text: &str) -> Vec<&str> {
+    // Versioned documents keep events under "events"; older ones are a bare array
+    let list_start = match text.find("\"events\"") {
+        Some(key) => text[key..].find('[').map(|i| key + i),
+        None => text.find('['),
+    };
+    let Some(list_start) = list_start else {
+        return Vec::new();
+    };
+
+    let mut objects = Vec::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut object_start = None;
+
+    for (i, c) in text.char_indices().skip_while(|&(i, _)| i <= list_start) {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => {
+                if depth == 0 && c == '{' {
+                    object_start = Some(i);
+                }
+                depth += 1;
+            }
+            '}' | ']' => {
+                // Closing bracket of the event list itself
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+                if depth == 0 {
+                    if let Some(start) = object_start.take() {
+                        objects.push(&text[start..=i]);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(start) = object_start {
+        objects.push(&text[start..]);
+    }
+    objects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_utf8_points_at_the_first_bad_byte() {
+        let bytes = b"[\n  {\"title\": \"Caf\xc3\xa9 \xff\"}\n]".to_vec();
+        let err = std::str::from_utf8(&bytes).unwrap_err();
+        let data = CorruptData::invalid_utf8(&bytes, err);
+        assert_eq!((data.line, data.column), (2, 19));
+        assert_eq!(data.raw, "[\n  {\"title\": \"Café \u{fffd}\"}\n]");
+        assert_eq!(
+            data.excerpt().as_deref(),
+            Some("  {\"title\": \"Café \u{fffd}\"}")
+        );
+        assert!(!data.backed_up);
+    }
+}
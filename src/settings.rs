@@ -0,0 +1,83 @@
+//! App preferences, shared by every timeline (unlike the timeline's own metadata).
+
+use crate::layout::Orientation;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+
+/// Bounds for the zoom limits themselves, so the view can't be made unusable. Positions are
+/// `f32` fractions of the timeline's whole range, which run out of precision around the top
+/// of this range: on a panel about 1,000 points wide, the finest detail the view can show is
+/// the range divided by ten million. So deep-time timelines can't be zoomed in to single
+/// years; one reaching back 13.8 billion years bottoms out at about 1,400 years per point.
+pub const ZOOM_LIMIT_RANGE: RangeInclusive<f32> = 0.001..=10_000.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Ask before Ctrl+Click moves an event to the trash
+    pub confirm_delete: bool,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    /// Show the overview strip above the timeline
+    pub show_minimap: bool,
+    pub orientation: Orientation,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            confirm_delete: true,
+            min_zoom: 0.1,
+            max_zoom: 100.0,
+            show_minimap: true,
+            orientation: Orientation::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn zoom_range(&self) -> RangeInclusive<f32> {
+        self.min_zoom..=self.max_zoom.max(self.min_zoom)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn load() -> Self {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .and_then(|storage| storage.get_item("lifeline_settings").ok().flatten())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn save(&self) {
+        if let Ok(json) = serde_json::to_string(self) {
+            if let Some(window) = web_sys::window() {
+                if let Ok(Some(storage)) = window.local_storage() {
+                    let _ = storage.set_item("lifeline_settings", &json);
+                }
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load() -> Self {
+        crate::storage::settings_path()
+            .and_then(|path| crate::storage::read_if_exists(&path).ok().flatten())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self) {
+        let Some(path) = crate::storage::settings_path() else {
+            return;
+        };
+        let result = serde_json::to_string(self)
+            .map_err(std::io::Error::from)
+            .and_then(|json| crate::storage::write_atomically(&path, &json));
+        if let Err(err) = result {
+            eprintln!("Failed to save settings to {}: {}", path.display(), err);
+        }
+    }
+}
@@ -13,7 +13,6 @@ pub struct Star {
 pub struct Galaxy {
     pub pos_normalized: (f32, f32),
     pub size: f32,
-    pub rotation: f32,
     pub color_hue: u8, // 0=blue, 1=purple, 2=orange
     pub depth_layer: f32,
 }
@@ -30,30 +29,16 @@ pub fn generate_stars(count: usize) -> Vec<Star> {
     let mut stars = Vec::new();
 
     use std::collections::hash_map::RandomState;
-    use std::hash::{BuildHasher, Hash, Hasher};
+    use std::hash::BuildHasher;
 
     let random_state = RandomState::new();
 
     for i in 0..count {
-        let mut hasher = random_state.build_hasher();
-        i.hash(&mut hasher);
-        let hash1 = hasher.finish();
-
-        let mut hasher = random_state.build_hasher();
-        (i + 1000).hash(&mut hasher);
-        let hash2 = hasher.finish();
-
-        let mut hasher = random_state.build_hasher();
-        (i + 2000).hash(&mut hasher);
-        let hash3 = hasher.finish();
-
-        let mut hasher = random_state.build_hasher();
-        (i + 3000).hash(&mut hasher);
-        let hash4 = hasher.finish();
-
-        let mut hasher = random_state.build_hasher();
-        (i + 4000).hash(&mut hasher);
-        let hash5 = hasher.finish();
+        let hash1 = random_state.hash_one(i);
+        let hash2 = random_state.hash_one(i + 1000);
+        let hash3 = random_state.hash_one(i + 2000);
+        let hash4 = random_state.hash_one(i + 3000);
+        let hash5 = random_state.hash_one(i + 4000);
 
         let x = (hash1 % 10000) as f32 / 10000.0;
         let y = (hash2 % 10000) as f32 / 10000.0;
@@ -113,22 +98,14 @@ pub fn generate_galaxies(count: usize) -> Vec<Galaxy> {
     let mut galaxies = Vec::new();
 
     use std::collections::hash_map::RandomState;
-    use std::hash::{BuildHasher, Hash, Hasher};
+    use std::hash::BuildHasher;
 
     let random_state = RandomState::new();
 
     for i in 0..count {
-        let mut hasher = random_state.build_hasher();
-        (i + 5000).hash(&mut hasher);
-        let hash1 = hasher.finish();
-
-        let mut hasher = random_state.build_hasher();
-        (i + 6000).hash(&mut hasher);
-        let hash2 = hasher.finish();
-
-        let mut hasher = random_state.build_hasher();
-        (i + 7000).hash(&mut hasher);
-        let hash3 = hasher.finish();
+        let hash1 = random_state.hash_one(i + 5000);
+        let hash2 = random_state.hash_one(i + 6000);
+        let hash3 = random_state.hash_one(i + 7000);
 
         let x = (hash1 % 10000) as f32 / 10000.0;
         let y = (hash2 % 10000) as f32 / 10000.0;
@@ -150,13 +127,11 @@ pub fn generate_galaxies(count: usize) -> Vec<Galaxy> {
             ((hash3 % 40) + 30) as f32 // Furthest: 30-70px
         };
 
-        let rotation = ((hash1 % 628) as f32) / 100.0;
         let color_hue = (hash2 % 3) as u8;
 
         galaxies.push(Galaxy {
             pos_normalized: (x, y),
             size,
-            rotation,
             color_hue,
             depth_layer,
         });
@@ -169,22 +144,14 @@ pub fn generate_nebulas(count: usize) -> Vec<Nebula> {
     let mut nebulas = Vec::new();
 
     use std::collections::hash_map::RandomState;
-    use std::hash::{BuildHasher, Hash, Hasher};
+    use std::hash::BuildHasher;
 
     let random_state = RandomState::new();
 
     for i in 0..count {
-        let mut hasher = random_state.build_hasher();
-        (i + 8000).hash(&mut hasher);
-        let hash1 = hasher.finish();
-
-        let mut hasher = random_state.build_hasher();
-        (i + 9000).hash(&mut hasher);
-        let hash2 = hasher.finish();
-
-        let mut hasher = random_state.build_hasher();
-        (i + 10000).hash(&mut hasher);
-        let hash3 = hasher.finish();
+        let hash1 = random_state.hash_one(i + 8000);
+        let hash2 = random_state.hash_one(i + 9000);
+        let hash3 = random_state.hash_one(i + 10000);
 
         let x = (hash1 % 10000) as f32 / 10000.0;
         let y = (hash2 % 10000) as f32 / 10000.0;
@@ -294,7 +261,6 @@ pub fn render_galaxies(
     galaxies: &[Galaxy],
     painter: &egui::Painter,
     rect: egui::Rect,
-    time: f32,
     camera_offset_x: f32,
     camera_offset_y: f32,
     camera_zoom: f32,
@@ -336,9 +302,6 @@ pub fn render_galaxies(
                         _ => Color32::from_rgba_unmultiplied(200, 140, 100, 40), // Orange
                     };
 
-                    // Slow rotation
-                    let rotation = galaxy.rotation + time * 0.05;
-
                     // Draw spiral galaxy effect with multiple layers
                     for layer in 0..5 {
                         let layer_f = layer as f32;
@@ -405,7 +368,6 @@ pub fn render_nebulas(
                     && tiled_y >= rect.top() - 300.0
                     && tiled_y <= rect.bottom() + 300.0
                 {
-                    let pos = Pos2::new(tiled_x, tiled_y);
                     let size = nebula.size * zoom_factor;
 
                     // Subtle pulsing effect
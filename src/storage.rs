@@ -0,0 +1,98 @@
+//! Native persistence: the timeline lives as a JSON file in the platform data directory
+//! (e.g. `~/.local/share/lifeline` on Linux, `%APPDATA%\lifeline` on Windows).
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const APP_DIR: &str = "lifeline";
+const TIMELINE_FILE: &str = "timeline.json";
+const DOCUMENT_FILE: &str = "document.json";
+const RECENT_FILES_FILE: &str = "recent_files.json";
+const BACKUP_FILE: &str = "timeline.corrupt.json";
+const SETTINGS_FILE: &str = "settings.json";
+
+fn data_file(name: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_DIR).join(name))
+}
+
+/// The autosaved working copy of whatever timeline is open.
+pub fn timeline_path() -> Option<PathBuf> {
+    data_file(TIMELINE_FILE)
+}
+
+/// Where an unreadable working copy is kept before anything can overwrite it.
+pub fn backup_path() -> Option<PathBuf> {
+    data_file(BACKUP_FILE)
+}
+
+/// Which document file the working copy belongs to.
+pub fn document_path() -> Option<PathBuf> {
+    data_file(DOCUMENT_FILE)
+}
+
+pub fn recent_files_path() -> Option<PathBuf> {
+    data_file(RECENT_FILES_FILE)
+}
+
+pub fn settings_path() -> Option<PathBuf> {
+    data_file(SETTINGS_FILE)
+}
+
+/// Reads the file, treating a missing file as "nothing saved yet".
+pub fn read_if_exists(path: &Path) -> io::Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Like [`read_if_exists`], for data that may not be valid UTF-8.
+pub fn read_bytes_if_exists(path: &Path) -> io::Result<Option<Vec<u8>>> {
+    match fs::read(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Writes to a temporary file next to `path` and renames it into place, so a crash
+/// mid-write leaves either the old file or the new one, never a truncated mix.
+pub fn write_atomically(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = (|| {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(contents.as_ref())?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result?;
+    sync_parent_dir(path)
+}
+
+/// Flushes the directory entry, so a rename into it survives a crash as well.
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => fs::File::open(dir)?.sync_all(),
+        _ => Ok(()),
+    }
+}
+
+/// Directories can't be opened as files on other platforms, where the rename is enough.
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,387 @@
+//! The ruler along the bottom (or, when vertical, the right) of the timeline, with ticks
+//! that adapt to the zoom level.
+
+use crate::date::{self, Date};
+use crate::event_renderer::{get_month_name, Camera, TimelineLayout};
+use crate::layout::Orientation;
+use eframe::egui;
+use eframe::epaint::{Color32, FontId, Pos2, Stroke};
+
+const MIN_MINOR_SPACING: f32 = 12.0;
+
+/// Depth of the strip the axis is drawn in: its height, or its width when vertical (where
+/// the labels sit beside the ticks).
+pub fn thickness(orientation: Orientation) -> f32 {
+    match orientation {
+        Orientation::Horizontal => 34.0,
+        Orientation::Vertical => 96.0,
+    }
+}
+
+/// Closest major ticks may get, in points, so their labels don't overlap.
+fn min_major_spacing(orientation: Orientation) -> f32 {
+    match orientation {
+        Orientation::Horizontal => 120.0,
+        Orientation::Vertical => 48.0,
+    }
+}
+
+/// Distance between ticks, aligned to the calendar rather than to a fixed number of seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TickStep {
+    /// Aligned to multiples of itself since midnight UTC
+    Seconds(i64),
+    /// Days of the month: the 1st, then every n days
+    Days(u8),
+    Months(i64),
+    Years(i64),
+}
+
+impl TickStep {
+    /// Rough length, for choosing a step that fits the zoom level.
+    fn approx_seconds(self) -> f64 {
+        let day = date::SECONDS_PER_DAY as f64;
+        match self {
+            TickStep::Seconds(n) => n as f64,
+            TickStep::Days(n) => n as f64 * day,
+            TickStep::Months(n) => n as f64 * 30.44 * day,
+            TickStep::Years(n) => n as f64 * 365.25 * day,
+        }
+    }
+
+    /// Whether every tick of `self` also falls on a tick of `step`.
+    fn falls_on(self, step: TickStep) -> bool {
+        match (self, step) {
+            (TickStep::Seconds(a), TickStep::Seconds(b))
+            | (TickStep::Years(a), TickStep::Years(b)) => a % b == 0,
+            (TickStep::Months(a), TickStep::Months(b)) => a % b == 0,
+            (TickStep::Days(a), TickStep::Days(b)) => a % b == 0,
+            // A coarser unit always starts on a tick of a finer one
+            _ => true,
+        }
+    }
+}
+
+/// Every step the axis can use, finest first: minutes and hours, days, months, then years
+/// in a 1-2-5 sequence up to the age of the universe.
+fn tick_steps() -> Vec<TickStep> {
+    let mut steps: Vec<TickStep> = [60, 300, 900, 1800, 3600, 3 * 3600, 6 * 3600, 12 * 3600]
+        .into_iter()
+        .map(TickStep::Seconds)
+        .collect();
+    steps.extend([1, 2, 5, 10].map(TickStep::Days));
+    steps.extend([1, 3, 6].map(TickStep::Months));
+    let mut magnitude = 1;
+    while magnitude <= 10_000_000_000 {
+        steps.extend([1, 2, 5].map(|n| TickStep::Years(n * magnitude)));
+        magnitude *= 10;
+    }
+    steps
+}
+
+/// Timestamps of the ticks of `step` between `start` and `end`.
+fn ticks(step: TickStep, start: i64, end: i64) -> Vec<i64> {
+    let mut ticks = Vec::new();
+    let start_date = Date::from_days_since_epoch(start.div_euclid(date::SECONDS_PER_DAY));
+    match step {
+        TickStep::Seconds(n) => {
+            let mut tick = start.div_euclid(n) * n;
+            while tick <= end {
+                ticks.push(tick);
+                tick += n;
+            }
+        }
+        TickStep::Days(n) => {
+            let mut day = start.div_euclid(date::SECONDS_PER_DAY);
+            while day * date::SECONDS_PER_DAY <= end {
+                let date = Date::from_days_since_epoch(day);
+                // Skip ticks too close to the 1st of the next month
+                let days_in_month = Date::days_in_month(date.year, date.month);
+                if (date.day - 1).is_multiple_of(n)
+                    && (date.day == 1 || date.day + n / 2 <= days_in_month)
+                {
+                    ticks.push(day * date::SECONDS_PER_DAY);
+                }
+                day += 1;
+            }
+        }
+        TickStep::Months(n) => {
+            let first = start_date.year * 12 + start_date.month as i64 - 1;
+            let mut month = first.div_euclid(n) * n;
+            loop {
+                let date = Date::new(month.div_euclid(12), (month.rem_euclid(12) + 1) as u8, 1);
+                let tick = date.days_since_epoch() * date::SECONDS_PER_DAY;
+                if tick > end {
+                    break;
+                }
+                ticks.push(tick);
+                month += n;
+            }
+        }
+        TickStep::Years(n) => {
+            let mut year = start_date.year.div_euclid(n) * n;
+            loop {
+                let tick = Date::new(year, 1, 1).days_since_epoch() * date::SECONDS_PER_DAY;
+                if tick > end {
+                    break;
+                }
+                ticks.push(tick);
+                year += n;
+            }
+        }
+    }
+    ticks
+}
+
+fn tick_label(step: TickStep, timestamp: i64) -> String {
+    let days = timestamp.div_euclid(date::SECONDS_PER_DAY);
+    let date = Date::from_days_since_epoch(days);
+    let month = &get_month_name(date.month)[..3];
+    let day_label = || format!("{} {}, {}", month, date.day, date::format_year(date.year));
+    match step {
+        TickStep::Seconds(_) => {
+            let seconds = timestamp.rem_euclid(date::SECONDS_PER_DAY);
+            if seconds == 0 {
+                day_label()
+            } else {
+                format!("{:02}:{:02}", seconds / 3600, seconds % 3600 / 60)
+            }
+        }
+        TickStep::Days(_) => day_label(),
+        TickStep::Months(_) => format!("{} {}", month, date::format_year(date.year)),
+        TickStep::Years(_) => date::format_year(date.year),
+    }
+}
+
+/// Draws the time axis along the far edge of the panel, with faint grid lines at the major
+/// ticks. Works in layout space, like the rest of the layout, and projects onto the screen.
+pub fn render_time_axis(painter: &egui::Painter, layout: &TimelineLayout, camera: &Camera) {
+    let rect = layout.rect;
+    let projection = layout.projection;
+    let orientation = projection.orientation;
+    // Screen x to timestamp, through the camera and then the layout
+    let timestamp_at = |x: f32| layout.timestamp_at((x - camera.offset_x) / camera.zoom);
+    let screen_x = |timestamp: i64| layout.x(timestamp) * camera.zoom + camera.offset_x;
+
+    // Keep years within the range dates can represent
+    let limit = date::MAX_YEAR_MAGNITUDE * 366 * date::SECONDS_PER_DAY;
+    let start = timestamp_at(rect.left()).clamp(-limit, limit);
+    let end = timestamp_at(rect.right()).clamp(-limit, limit);
+    let seconds_per_point = (end - start) as f64 / rect.width().max(1.0) as f64;
+    if seconds_per_point <= 0.0 {
+        return;
+    }
+
+    let steps = tick_steps();
+    let spacing = |step: TickStep| (step.approx_seconds() / seconds_per_point) as f32;
+    let Some(major) = steps
+        .iter()
+        .copied()
+        .find(|&step| spacing(step) >= min_major_spacing(orientation))
+    else {
+        return;
+    };
+    let minor = steps
+        .iter()
+        .copied()
+        .take_while(|&step| step != major)
+        .find(|&step| spacing(step) >= MIN_MINOR_SPACING && major.falls_on(step));
+
+    let strip_top = rect.bottom() - thickness(orientation);
+    let axis_y = strip_top + 8.0;
+    let line = |x1: f32, y1: f32, x2: f32, y2: f32, stroke: Stroke| {
+        painter.line_segment(
+            [
+                projection.to_screen(Pos2::new(x1, y1)),
+                projection.to_screen(Pos2::new(x2, y2)),
+            ],
+            stroke,
+        );
+    };
+
+    painter.rect_filled(
+        projection.rect_to_screen(egui::Rect::from_min_max(
+            Pos2::new(rect.left(), strip_top),
+            rect.max,
+        )),
+        0.0,
+        Color32::from_black_alpha(120),
+    );
+    line(
+        rect.left(),
+        axis_y,
+        rect.right(),
+        axis_y,
+        Stroke::new(1.0, Color32::from_white_alpha(80)),
+    );
+
+    if let Some(minor) = minor {
+        for tick in ticks(minor, start, end) {
+            let x = screen_x(tick);
+            line(
+                x,
+                axis_y - 4.0,
+                x,
+                axis_y,
+                Stroke::new(1.0, Color32::from_white_alpha(60)),
+            );
+        }
+    }
+
+    // Labels hang below the axis, or sit to its right when vertical
+    let label_align = match orientation {
+        Orientation::Horizontal => egui::Align2::CENTER_TOP,
+        Orientation::Vertical => egui::Align2::LEFT_CENTER,
+    };
+    for tick in ticks(major, start, end) {
+        let x = screen_x(tick);
+        line(
+            x,
+            rect.top(),
+            x,
+            axis_y - 8.0,
+            Stroke::new(1.0, Color32::from_white_alpha(10)),
+        );
+        line(
+            x,
+            axis_y - 8.0,
+            x,
+            axis_y,
+            Stroke::new(1.0, Color32::from_white_alpha(160)),
+        );
+        painter.text(
+            projection.to_screen(Pos2::new(x, axis_y + 3.0)),
+            label_align,
+            tick_label(major, tick),
+            FontId::proportional(11.0),
+            Color32::from_gray(190),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i64, month: u8, day: u8) -> i64 {
+        Date::new(year, month, day).days_since_epoch() * date::SECONDS_PER_DAY
+    }
+
+    fn dates(ticks: &[i64]) -> Vec<(i64, u8, u8)> {
+        ticks
+            .iter()
+            .map(|&t| {
+                let date = Date::from_days_since_epoch(t.div_euclid(date::SECONDS_PER_DAY));
+                (date.year, date.month, date.day)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn steps_run_from_fine_to_coarse() {
+        let steps = tick_steps();
+        assert!(steps
+            .windows(2)
+            .all(|pair| pair[0].approx_seconds() < pair[1].approx_seconds()));
+        // Enough to span the age of the universe in a few ticks
+        assert!(steps.last().unwrap().approx_seconds() > 13.8e9 * 365.25 * 86_400.0);
+    }
+
+    #[test]
+    fn day_ticks_skip_days_near_the_end_of_the_month() {
+        let found = ticks(TickStep::Days(10), at(2024, 1, 1), at(2024, 3, 1));
+        assert_eq!(
+            dates(&found),
+            vec![
+                (2024, 1, 1),
+                (2024, 1, 11),
+                (2024, 1, 21),
+                // Not January 31st, a day before February 1st
+                (2024, 2, 1),
+                (2024, 2, 11),
+                (2024, 2, 21),
+                (2024, 3, 1),
+            ]
+        );
+
+        let found = ticks(TickStep::Days(2), at(2023, 1, 27), at(2023, 2, 1));
+        assert_eq!(
+            dates(&found),
+            vec![(2023, 1, 27), (2023, 1, 29), (2023, 2, 1)]
+        );
+    }
+
+    #[test]
+    fn month_ticks_cross_years() {
+        let found = ticks(TickStep::Months(3), at(2023, 11, 15), at(2024, 7, 1));
+        assert_eq!(
+            dates(&found),
+            vec![(2023, 10, 1), (2024, 1, 1), (2024, 4, 1), (2024, 7, 1)]
+        );
+    }
+
+    #[test]
+    fn year_ticks_align_before_year_one() {
+        // Astronomical years, so 250 BCE is -249; ticks round down to a multiple of 100
+        let found = ticks(TickStep::Years(100), at(-249, 6, 1), at(101, 1, 1));
+        assert_eq!(
+            dates(&found),
+            vec![
+                (-300, 1, 1),
+                (-200, 1, 1),
+                (-100, 1, 1),
+                (0, 1, 1),
+                (100, 1, 1)
+            ]
+        );
+
+        let found = ticks(TickStep::Seconds(3600), -5400, 0);
+        assert_eq!(found, vec![-7200, -3600, 0]);
+    }
+
+    #[test]
+    fn major_ticks_fall_on_minor_ones() {
+        let cases = [
+            (
+                TickStep::Days(10),
+                TickStep::Days(5),
+                at(2024, 1, 1),
+                at(2024, 4, 1),
+            ),
+            (
+                TickStep::Months(6),
+                TickStep::Months(3),
+                at(2020, 1, 1),
+                at(2024, 1, 1),
+            ),
+            (
+                TickStep::Months(1),
+                TickStep::Days(5),
+                at(2023, 1, 1),
+                at(2024, 1, 1),
+            ),
+            (
+                TickStep::Years(100),
+                TickStep::Years(50),
+                at(-1000, 1, 1),
+                at(1000, 1, 1),
+            ),
+            (
+                TickStep::Days(1),
+                TickStep::Seconds(6 * 3600),
+                at(2024, 1, 1),
+                at(2024, 1, 9),
+            ),
+        ];
+        for (major, minor, start, end) in cases {
+            assert!(major.falls_on(minor), "{:?} on {:?}", major, minor);
+            let minor_ticks = ticks(minor, start, end);
+            for tick in ticks(major, start, end) {
+                assert!(minor_ticks.contains(&tick), "{:?} on {:?}", major, minor);
+            }
+        }
+
+        assert!(!TickStep::Years(2).falls_on(TickStep::Years(5)));
+        assert!(!TickStep::Days(5).falls_on(TickStep::Days(2)));
+    }
+}
@@ -1,50 +1,181 @@
+use crate::category::{self, Category, CategoryId};
+use crate::date::{self, Date, DatePrecision, TimeOfDay, TimeZone};
+use crate::history::History;
+use crate::lane::{Lane, LaneId};
+use crate::layout::LayoutMode;
+use crate::migrations;
+use crate::recovery;
 use eframe::epaint::Color32;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
-#[cfg(not(target_arch = "wasm32"))]
-use std::time::SystemTime;
-
-#[cfg(target_arch = "wasm32")]
-use web_time::SystemTime;
+/// Stable identifier of an event within its timeline; unlike its index it survives
+/// inserts, deletes and re-sorting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EventId(pub u64);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SerializableEvent {
+    /// Missing in data saved before IDs existed; such events get a fresh one on load
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<EventId>,
     pub title: String,
     pub description: String,
     pub day: u8,
     pub month: u8,
-    pub year: i32,
+    pub year: i64,
+    /// Missing in data saved before precision existed, which was always day-precise
+    #[serde(default)]
+    pub precision: DatePrecision,
+    #[serde(default)]
+    pub circa: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hour: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minute: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub second: Option<u8>,
+    /// UTC offset like "UTC+02:00" or an IANA zone name like "Europe/Oslo"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// Last day of a span event; all three are set together
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_day: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_month: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_year: Option<i64>,
+    #[serde(default)]
+    pub end_precision: DatePrecision,
     pub image_path: Option<String>,
     pub color: [u8; 4], // [r, g, b, a]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<CategoryId>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lane: Option<LaneId>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Event {
+    /// Assigned by [`Timeline::add_event`]
+    pub id: EventId,
     pub title: String,
     pub description: String,
-    pub timestamp: SystemTime,
+    /// Seconds since 1970-01-01T00:00:00Z, derived from the date, time and timezone
+    pub timestamp: i64,
     pub day: u8,
     pub month: u8,
-    pub year: i32,
+    pub year: i64,
+    /// Fields finer than this are placeholders (1 for an unknown day or month)
+    pub precision: DatePrecision,
+    /// "Circa": the date itself is only approximate
+    pub circa: bool,
+    pub time: Option<TimeOfDay>,
+    pub timezone: Option<TimeZone>,
+    /// Last day (inclusive) of a span event such as a job or a trip
+    pub end_date: Option<Date>,
+    pub end_precision: DatePrecision,
+    /// Seconds since the epoch at the end of `end_date`
+    pub end_timestamp: Option<i64>,
     pub image_path: Option<String>,
+    /// Used when the event has no category (or its category was removed)
     pub color: Color32,
+    pub category: Option<CategoryId>,
+    pub tags: Vec<String>,
+    /// Events without a lane (or whose lane was removed) share a band of their own
+    pub lane: Option<LaneId>,
+}
+
+impl SerializableEvent {
+    /// Why the saved date, time or timezone can't be used, if they can't. The form never
+    /// produces such values (see [`EventInput::validate`]), but files can be edited by hand
+    /// or damaged.
+    fn date_error(&self) -> Option<String> {
+        let start = Date::new(self.year, self.month, self.day);
+        let end = match (self.end_day, self.end_month, self.end_year) {
+            (Some(day), Some(month), Some(year)) => Some(Date::new(year, month, day)),
+            _ => None,
+        };
+        if let Some(invalid) = std::iter::once(start)
+            .chain(end)
+            .find(|date| !date.is_valid())
+        {
+            return Some(format!(
+                "event \"{}\" has an invalid date: year {}, month {}, day {}",
+                self.title, invalid.year, invalid.month, invalid.day
+            ));
+        }
+
+        let time_valid = match self.hour {
+            Some(hour) => {
+                TimeOfDay::new(hour, self.minute.unwrap_or(0), self.second.unwrap_or(0)).is_some()
+            }
+            None => self.minute.is_none() && self.second.is_none(),
+        };
+        if !time_valid {
+            return Some(format!(
+                "event \"{}\" has an invalid time: hour {:?}, minute {:?}, second {:?}",
+                self.title, self.hour, self.minute, self.second
+            ));
+        }
+
+        match &self.timezone {
+            Some(zone) if TimeZone::parse(zone).is_none() => Some(format!(
+                "event \"{}\" has an unknown timezone \"{}\"",
+                self.title, zone
+            )),
+            _ => None,
+        }
+    }
 }
 
 impl Event {
     pub fn to_serializable(&self) -> SerializableEvent {
         SerializableEvent {
+            id: Some(self.id),
             title: self.title.clone(),
             description: self.description.clone(),
             day: self.day,
             month: self.month,
             year: self.year,
+            precision: self.precision,
+            circa: self.circa,
+            hour: self.time.map(|t| t.hour),
+            minute: self.time.map(|t| t.minute),
+            second: self.time.map(|t| t.second),
+            timezone: self.timezone.map(|tz| tz.to_string()),
+            end_day: self.end_date.map(|d| d.day),
+            end_month: self.end_date.map(|d| d.month),
+            end_year: self.end_date.map(|d| d.year),
+            end_precision: self.end_precision,
             image_path: self.image_path.clone(),
             color: self.color.to_array(),
+            category: self.category,
+            tags: self.tags.clone(),
+            lane: self.lane,
         }
     }
 
     pub fn from_serializable(s: SerializableEvent) -> Self {
-        Self::new(s.title, s.description, s.day, s.month, s.year, s.image_path)
+        let time = s
+            .hour
+            .and_then(|hour| TimeOfDay::new(hour, s.minute.unwrap_or(0), s.second.unwrap_or(0)));
+        let timezone = s.timezone.as_deref().and_then(TimeZone::parse);
+        let end_date = match (s.end_day, s.end_month, s.end_year) {
+            (Some(day), Some(month), Some(year)) => Some(Date::new(year, month, day)),
+            _ => None,
+        };
+        let mut event = Self::new(s.title, s.description, s.day, s.month, s.year, s.image_path)
+            .with_precision(s.precision, s.circa)
+            .with_time(time, timezone)
+            .with_end_date(end_date, s.end_precision);
+        event.category = s.category;
+        event.tags = s.tags;
+        event.lane = s.lane;
+        event
     }
 }
 
@@ -54,76 +185,1533 @@ impl Event {
         description: String,
         day: u8,
         month: u8,
-        year: i32,
+        year: i64,
         image_path: Option<String>,
     ) -> Self {
-        // Convert date to timestamp (approximate)
-        let now = SystemTime::now();
-        let current_year = 2026;
-        let years_ago = current_year - year;
-
-        let timestamp = if years_ago >= 0 {
-            now - std::time::Duration::from_secs((years_ago as u64) * 365 * 24 * 60 * 60)
-        } else {
-            now + std::time::Duration::from_secs(((-years_ago) as u64) * 365 * 24 * 60 * 60)
-        };
+        let timestamp = date::instant_seconds(Date::new(year, month, day), None, None);
 
         Self {
+            id: EventId(0),
             title,
             description,
             timestamp,
             day,
             month,
             year,
+            precision: DatePrecision::Day,
+            circa: false,
+            time: None,
+            timezone: None,
+            end_date: None,
+            end_precision: DatePrecision::Day,
+            end_timestamp: None,
             image_path,
             color: Color32::from_rgb(100, 150, 255),
+            category: None,
+            tags: Vec::new(),
+            lane: None,
+        }
+    }
+
+    /// Marks how much of the date is known, recomputing the timestamp.
+    pub fn with_precision(mut self, precision: DatePrecision, circa: bool) -> Self {
+        self.precision = precision;
+        self.circa = circa;
+        self.recompute_timestamps();
+        self
+    }
+
+    /// Sets the time of day and timezone, recomputing the timestamp.
+    pub fn with_time(mut self, time: Option<TimeOfDay>, timezone: Option<TimeZone>) -> Self {
+        self.time = time;
+        self.timezone = timezone;
+        if time.is_some() {
+            self.precision = DatePrecision::Time;
+        } else if self.precision == DatePrecision::Time {
+            self.precision = DatePrecision::Day;
+        }
+        self.recompute_timestamps();
+        self
+    }
+
+    /// Turns the event into a span ending on `end_date`, or back into a point with `None`.
+    pub fn with_end_date(mut self, end_date: Option<Date>, end_precision: DatePrecision) -> Self {
+        self.end_date = end_date;
+        self.end_precision = end_precision;
+        self.recompute_timestamps();
+        self
+    }
+
+    pub fn date(&self) -> Date {
+        Date::new(self.year, self.month, self.day)
+    }
+
+    /// Whether the renderer should blur the event: approximate or coarser than a day.
+    pub fn is_uncertain(&self) -> bool {
+        self.circa || self.precision < DatePrecision::Day
+    }
+
+    /// Half the width of the window the event could fall in; zero for exact dates.
+    pub fn uncertainty_seconds(&self) -> i64 {
+        let (first, next) = self.precision.day_range(self.date());
+        let half_window = if self.precision < DatePrecision::Day {
+            (next - first) * date::SECONDS_PER_DAY / 2
+        } else {
+            0
+        };
+        if self.circa {
+            half_window.max(date::SECONDS_PER_DAY) * 3
+        } else {
+            half_window
+        }
+    }
+
+    pub fn is_span(&self) -> bool {
+        self.end_timestamp.is_some()
+    }
+
+    /// The latest instant covered by the event.
+    pub fn last_timestamp(&self) -> i64 {
+        self.end_timestamp.unwrap_or(self.timestamp)
+    }
+
+    fn recompute_timestamps(&mut self) {
+        let timezone = self.timezone.as_ref();
+        self.timestamp = if self.precision < DatePrecision::Day {
+            // Coarse dates sit in the middle of the period they cover
+            let (first, next) = self.precision.day_range(self.date());
+            date::instant_seconds(Date::from_days_since_epoch(first), None, timezone)
+                + (next - first) * date::SECONDS_PER_DAY / 2
+        } else {
+            date::instant_seconds(self.date(), self.time, timezone)
+        };
+        // Spans run to the end of the last day, month, season or year they cover
+        self.end_timestamp = self.end_date.map(|end| {
+            let (_, next) = self.end_precision.day_range(end);
+            date::instant_seconds(Date::from_days_since_epoch(next), None, timezone)
+        });
+    }
+}
+
+/// Problems with user-entered event fields, reported by [`EventInput::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    EmptyTitle,
+    InvalidYear(String),
+    MonthOutOfRange(String),
+    DayWithoutMonth,
+    InvalidDay { day: String, month: u8, year: i64 },
+    InvalidTime(String),
+    TimeWithoutDay,
+    UnknownTimeZone(String),
+    TimeZoneWithoutTime,
+    InvalidEndDate(Box<ValidationError>),
+    EndBeforeStart,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::EmptyTitle => write!(f, "Title cannot be empty"),
+            ValidationError::InvalidYear(year) => write!(
+                f,
+                "\"{}\" is not a valid year (try 1990, 44 BCE or 66 Ma)",
+                year
+            ),
+            ValidationError::MonthOutOfRange(month) => {
+                write!(
+                    f,
+                    "Month must be a number from 1 to 12 or a season, got \"{}\"",
+                    month
+                )
+            }
+            ValidationError::DayWithoutMonth => {
+                write!(f, "A day can only be given together with a numeric month")
+            }
+            ValidationError::InvalidDay { day, month, year } => write!(
+                f,
+                "Day must be between 1 and {} for {}/{}, got \"{}\"",
+                Date::days_in_month(*year, *month),
+                month,
+                date::format_year(*year),
+                day
+            ),
+            ValidationError::InvalidTime(time) => {
+                write!(f, "Time must look like HH:MM or HH:MM:SS, got \"{}\"", time)
+            }
+            ValidationError::TimeWithoutDay => write!(f, "A time needs a full day, month and year"),
+            ValidationError::TimeZoneWithoutTime => {
+                write!(f, "A time zone can only be given together with a time")
+            }
+            ValidationError::UnknownTimeZone(zone) => write!(
+                f,
+                "\"{}\" is not a UTC offset (like +02:00) or a zone name (like Europe/Oslo)",
+                zone
+            ),
+            ValidationError::InvalidEndDate(error) => write!(f, "End date: {}", error),
+            ValidationError::EndBeforeStart => write!(f, "End date is before the start date"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Raw text from the event form, before validation.
+#[derive(Debug, Clone, Default)]
+pub struct EventInput {
+    pub title: String,
+    pub description: String,
+    pub day: String,
+    pub month: String,
+    pub year: String,
+    pub circa: bool,
+    /// Optional, `HH:MM` or `HH:MM:SS`
+    pub time: String,
+    /// Optional, a UTC offset or IANA zone name
+    pub timezone: String,
+    /// Optional; filling these in makes the event a span
+    pub end_day: String,
+    pub end_month: String,
+    pub end_year: String,
+    pub image_path: String,
+    pub category: Option<CategoryId>,
+    /// Comma-separated
+    pub tags: String,
+    pub lane: Option<LaneId>,
+}
+
+impl EventInput {
+    /// Pre-fills the form from an existing event, so that validating it unchanged
+    /// gives back the same event.
+    pub fn from_event(event: &Event) -> Self {
+        let (day, month, year) = date_fields(event.date(), event.precision);
+        let (end_day, end_month, end_year) = event
+            .end_date
+            .map(|end| date_fields(end, event.end_precision))
+            .unwrap_or_default();
+
+        Self {
+            title: event.title.clone(),
+            description: event.description.clone(),
+            day,
+            month,
+            year,
+            circa: event.circa,
+            time: event.time.map(|t| t.to_string()).unwrap_or_default(),
+            timezone: event.timezone.map(|tz| tz.to_string()).unwrap_or_default(),
+            end_day,
+            end_month,
+            end_year,
+            image_path: event.image_path.clone().unwrap_or_default(),
+            category: event.category,
+            tags: event.tags.join(", "),
+            lane: event.lane,
+        }
+    }
+
+    pub fn set_date(&mut self, date: Date) {
+        self.day = date.day.to_string();
+        self.month = date.month.to_string();
+        self.year = year_field(date.year);
+    }
+
+    /// Builds an event from the form fields, or returns every problem found.
+    pub fn validate(&self) -> Result<Event, Vec<ValidationError>> {
+        let mut errors = Vec::new();
+
+        if self.title.trim().is_empty() {
+            errors.push(ValidationError::EmptyTitle);
+        }
+
+        let start = match parse_date(&self.day, &self.month, &self.year) {
+            Ok(parsed) => Some(parsed),
+            Err(date_errors) => {
+                errors.extend(date_errors);
+                None
+            }
+        };
+
+        let has_end = [&self.end_day, &self.end_month, &self.end_year]
+            .iter()
+            .any(|field| !field.trim().is_empty());
+        let end = if has_end {
+            match parse_date(&self.end_day, &self.end_month, &self.end_year) {
+                Ok(parsed) => Some(parsed),
+                Err(date_errors) => {
+                    errors.extend(
+                        date_errors
+                            .into_iter()
+                            .map(|e| ValidationError::InvalidEndDate(Box::new(e))),
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Compare the end of the end period with the start of the start period
+        if let (Some((start, start_precision)), Some((end, end_precision))) = (start, end) {
+            if end_precision.day_range(end).1 <= start_precision.day_range(start).0 {
+                errors.push(ValidationError::EndBeforeStart);
+            }
+        }
+
+        let time = optional_field(&self.time, TimeOfDay::parse);
+        if time.is_err() {
+            errors.push(ValidationError::InvalidTime(self.time.clone()));
+        }
+        // A time of day only makes sense on a known day
+        if let (Some((_, precision)), Ok(Some(_))) = (start, &time) {
+            if precision < DatePrecision::Day {
+                errors.push(ValidationError::TimeWithoutDay);
+            }
+        }
+
+        let timezone = optional_field(&self.timezone, TimeZone::parse);
+        if timezone.is_err() {
+            errors.push(ValidationError::UnknownTimeZone(self.timezone.clone()));
+        }
+        // Without a time it would silently shift local midnight, and never be shown
+        if let (Ok(None), Ok(Some(_))) = (&time, &timezone) {
+            errors.push(ValidationError::TimeZoneWithoutTime);
+        }
+
+        match (start, time, timezone) {
+            (Some(start), Ok(time), Ok(timezone)) if errors.is_empty() => {
+                let image_path = if self.image_path.is_empty() {
+                    None
+                } else {
+                    Some(self.image_path.clone())
+                };
+                let (start, precision) = start;
+                let (end_date, end_precision) = match end {
+                    Some((end, end_precision)) => (Some(end), end_precision),
+                    None => (None, DatePrecision::Day),
+                };
+                let mut event = Event::new(
+                    self.title.clone(),
+                    self.description.clone(),
+                    start.day,
+                    start.month,
+                    start.year,
+                    image_path,
+                )
+                .with_precision(precision, self.circa)
+                .with_time(time, timezone)
+                .with_end_date(end_date, end_precision);
+                event.category = self.category;
+                event.tags = category::parse_tags(&self.tags);
+                event.lane = self.lane;
+                Ok(event)
+            }
+            _ => Err(errors),
         }
     }
+}
+
+/// The inverse of [`parse_date`]: day, month and year text for a date of some precision.
+fn date_fields(date: Date, precision: DatePrecision) -> (String, String, String) {
+    let month = match precision {
+        DatePrecision::Year => String::new(),
+        DatePrecision::Season => date::season_name(date.month).to_lowercase(),
+        _ => date.month.to_string(),
+    };
+    let day = if precision >= DatePrecision::Day {
+        date.day.to_string()
+    } else {
+        String::new()
+    };
+    (day, month, year_field(date.year))
+}
 
-    pub fn today(title: String, description: String) -> Self {
-        Self::new(title, description, 16, 1, 2026, None)
+/// A year as shown elsewhere (e.g. "44 BCE" rather than the astronomical -43), unless that
+/// is rounded (as deep time is) and wouldn't give back the same year.
+fn year_field(year: i64) -> String {
+    let shown = date::format_year(year);
+    if date::parse_year(&shown) == Some(year) {
+        shown
+    } else if year <= 0 {
+        format!("{} BCE", 1 - year)
+    } else {
+        year.to_string()
     }
 }
 
+/// Parses a possibly partial date. Leaving the day out gives month precision, leaving both
+/// day and month out gives year precision, and a season name in place of the month gives
+/// season precision. Unknown fields are stored as 1.
+fn parse_date(
+    day: &str,
+    month: &str,
+    year: &str,
+) -> Result<(Date, DatePrecision), Vec<ValidationError>> {
+    let mut errors = Vec::new();
+
+    let parsed_year = date::parse_year(year);
+    if parsed_year.is_none() {
+        errors.push(ValidationError::InvalidYear(year.to_string()));
+    }
+
+    let day_given = !day.trim().is_empty();
+    let parsed_month = if month.trim().is_empty() {
+        if day_given {
+            errors.push(ValidationError::DayWithoutMonth);
+            None
+        } else {
+            Some((1, DatePrecision::Year))
+        }
+    } else if let Some(season_month) = date::season_start_month(month) {
+        if day_given {
+            errors.push(ValidationError::DayWithoutMonth);
+            None
+        } else {
+            Some((season_month, DatePrecision::Season))
+        }
+    } else {
+        let numeric = month
+            .trim()
+            .parse::<u8>()
+            .ok()
+            .filter(|m| (1..=12).contains(m));
+        if numeric.is_none() {
+            errors.push(ValidationError::MonthOutOfRange(month.to_string()));
+        }
+        numeric.map(|m| (m, DatePrecision::Month))
+    };
+
+    let (Some(year), Some((month, precision))) = (parsed_year, parsed_month) else {
+        return Err(errors);
+    };
+    if precision != DatePrecision::Month || !day_given {
+        return Ok((Date::new(year, month, 1), precision));
+    }
+
+    // The day can only be checked once we know which month (and year, for February) it's in
+    let parsed_day = day
+        .trim()
+        .parse::<u8>()
+        .ok()
+        .filter(|d| (1..=Date::days_in_month(year, month)).contains(d));
+    match parsed_day {
+        Some(day) => Ok((Date::new(year, month, day), DatePrecision::Day)),
+        None => {
+            errors.push(ValidationError::InvalidDay {
+                day: day.to_string(),
+                month,
+                year,
+            });
+            Err(errors)
+        }
+    }
+}
+
+/// Empty means "not given"; anything else has to parse.
+fn optional_field<T>(text: &str, parse: impl Fn(&str) -> Option<T>) -> Result<Option<T>, ()> {
+    if text.trim().is_empty() {
+        Ok(None)
+    } else {
+        parse(text).map(Some).ok_or(())
+    }
+}
+
+/// Information about the timeline as a whole, saved alongside its events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineMetadata {
+    pub name: String,
+    /// Seconds since the Unix epoch
+    pub created: i64,
+    /// Seconds since the Unix epoch; bumped on every change to the events
+    pub modified: i64,
+}
+
+/// A deleted event, kept until it is restored or purged.
+#[derive(Debug, Clone)]
+pub struct TrashedEvent {
+    pub event: Event,
+    /// Seconds since the Unix epoch
+    pub deleted: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedTrashEntry {
+    #[serde(flatten)]
+    event: SerializableEvent,
+    deleted: i64,
+}
+
+/// On-disk layout of a timeline; see [`crate::migrations`] for older versions.
+#[derive(Serialize, Deserialize)]
+struct SavedTimeline {
+    version: u64,
+    metadata: TimelineMetadata,
+    events: Vec<SerializableEvent>,
+    trash: Vec<SavedTrashEntry>,
+    categories: Vec<Category>,
+    layout: LayoutMode,
+    lanes: Vec<Lane>,
+}
+
+/// A reversible change to a timeline, holding the events as they were before and after.
+enum Command {
+    Add(Event),
+    /// Moved to the trash
+    Remove(TrashedEvent),
+    Edit {
+        before: Event,
+        after: Event,
+    },
+    Import(Vec<Event>),
+    /// Moved back out of the trash
+    Restore(TrashedEvent),
+    /// Deleted from the trash for good
+    Purge(Vec<TrashedEvent>),
+    SetCategories {
+        before: Vec<Category>,
+        after: Vec<Category>,
+    },
+    SetLanes {
+        before: Vec<Lane>,
+        after: Vec<Lane>,
+    },
+}
+
+/// Largest event ID kept when loading; see [`Timeline::from_saved`].
+const MAX_SAVED_ID: u64 = u64::MAX / 2;
+
 pub struct Timeline {
     events: Vec<Event>,
+    /// Oldest deletion first
+    trash: Vec<TrashedEvent>,
+    categories: Vec<Category>,
+    layout: LayoutMode,
+    /// In display order, top to bottom
+    lanes: Vec<Lane>,
+    next_id: u64,
+    metadata: TimelineMetadata,
+    history: History<Command>,
 }
 
 impl Timeline {
     pub fn new() -> Self {
-        Self { events: Vec::new() }
+        let now = date::now_seconds();
+        Self {
+            events: Vec::new(),
+            trash: Vec::new(),
+            categories: category::default_palette(),
+            layout: LayoutMode::default(),
+            lanes: Vec::new(),
+            next_id: 1,
+            metadata: TimelineMetadata {
+                name: crate::document::UNTITLED.to_string(),
+                created: now,
+                modified: now,
+            },
+            history: History::new(),
+        }
     }
 
-    pub fn add_event(&mut self, event: Event) {
-        self.events.push(event);
-        self.events.sort_by_key(|e| e.timestamp);
+    pub fn metadata(&self) -> &TimelineMetadata {
+        &self.metadata
     }
 
-    pub fn remove_event(&mut self, index: usize) {
-        if index < self.events.len() {
-            self.events.remove(index);
+    pub fn set_name(&mut self, name: String) {
+        self.metadata.name = name;
+    }
+
+    /// Adds the event under a fresh ID and returns that ID.
+    pub fn add_event(&mut self, event: Event) -> EventId {
+        let id = self.insert_new(event);
+        self.record(Command::Add(self.event(id).unwrap().clone()));
+        id
+    }
+
+    /// Adds all the events (e.g. from another timeline file) as a single undo step.
+    /// Categories and lanes are matched by name against `source_categories` and
+    /// `source_lanes`, those of the timeline the events came from. Events whose category
+    /// doesn't exist here keep its colour instead; those whose lane doesn't go in no lane.
+    pub fn import_events(
+        &mut self,
+        events: Vec<Event>,
+        source_categories: &[Category],
+        source_lanes: &[Lane],
+    ) -> Vec<EventId> {
+        let ids: Vec<EventId> = events
+            .into_iter()
+            .map(|mut event| {
+                let source_lane = event
+                    .lane
+                    .and_then(|id| source_lanes.iter().find(|l| l.id == id));
+                event.lane = source_lane
+                    .and_then(|source| self.lanes.iter().find(|l| l.name == source.name))
+                    .map(|local| local.id);
+                let source = event
+                    .category
+                    .and_then(|id| source_categories.iter().find(|c| c.id == id));
+                event.category = None;
+                if let Some(source) = source {
+                    match self.categories.iter().find(|c| c.name == source.name) {
+                        Some(local) => event.category = Some(local.id),
+                        None => event.color = source.color32(),
+                    }
+                }
+                self.insert_new(event)
+            })
+            .collect();
+        let imported = ids
+            .iter()
+            .filter_map(|&id| self.event(id).cloned())
+            .collect();
+        self.record(Command::Import(imported));
+        ids
+    }
+
+    pub fn event(&self, id: EventId) -> Option<&Event> {
+        self.events.iter().find(|e| e.id == id)
+    }
+
+    /// Replaces the event with the given ID, keeping that ID, and re-sorts.
+    /// Returns the previous version, or `None` if there is no such event.
+    pub fn update_event(&mut self, id: EventId, mut event: Event) -> Option<Event> {
+        let previous = self.take_event(id)?;
+        event.id = id;
+        self.insert_sorted(event.clone());
+        self.record(Command::Edit {
+            before: previous.clone(),
+            after: event,
+        });
+        Some(previous)
+    }
+
+    /// Moves the event to the trash.
+    pub fn remove_event(&mut self, id: EventId) -> Option<Event> {
+        let entry = TrashedEvent {
+            event: self.event(id)?.clone(),
+            deleted: date::now_seconds(),
+        };
+        self.move_to_trash(entry.clone());
+        self.record(Command::Remove(entry.clone()));
+        Some(entry.event)
+    }
+
+    pub fn trash(&self) -> &[TrashedEvent] {
+        &self.trash
+    }
+
+    /// Moves a trashed event back onto the timeline.
+    pub fn restore_event(&mut self, id: EventId) -> bool {
+        let Some(entry) = self.take_trashed(id) else {
+            return false;
+        };
+        self.insert_sorted(entry.event.clone());
+        self.record(Command::Restore(entry));
+        true
+    }
+
+    /// Deletes a trashed event for good (though it can still be undone this session).
+    pub fn purge_event(&mut self, id: EventId) -> bool {
+        let Some(entry) = self.take_trashed(id) else {
+            return false;
+        };
+        self.record(Command::Purge(vec![entry]));
+        true
+    }
+
+    pub fn empty_trash(&mut self) {
+        if self.trash.is_empty() {
+            return;
+        }
+        let entries = std::mem::take(&mut self.trash);
+        self.record(Command::Purge(entries));
+    }
+
+    pub fn categories(&self) -> &[Category] {
+        &self.categories
+    }
+
+    pub fn category(&self, id: CategoryId) -> Option<&Category> {
+        self.categories.iter().find(|c| c.id == id)
+    }
+
+    /// IDs of the categories, and any left on events (including trashed ones) by removed
+    /// categories.
+    pub fn category_ids_in_use(&self) -> impl Iterator<Item = CategoryId> + '_ {
+        let events = self
+            .events
+            .iter()
+            .chain(self.trash.iter().map(|t| &t.event));
+        self.categories
+            .iter()
+            .map(|c| c.id)
+            .chain(events.filter_map(|e| e.category))
+    }
+
+    /// Replaces the whole palette as one undo step. Events in a removed category fall back
+    /// to their own colour.
+    pub fn set_categories(&mut self, categories: Vec<Category>) {
+        if categories == self.categories {
+            return;
         }
+        let before = std::mem::replace(&mut self.categories, categories.clone());
+        self.record(Command::SetCategories {
+            before,
+            after: categories,
+        });
+    }
+
+    pub fn layout(&self) -> LayoutMode {
+        self.layout
+    }
+
+    /// Not an edit to the events, so it isn't recorded for undo.
+    pub fn set_layout(&mut self, layout: LayoutMode) {
+        self.layout = layout;
+    }
+
+    pub fn lanes(&self) -> &[Lane] {
+        &self.lanes
+    }
+
+    pub fn lane(&self, id: LaneId) -> Option<&Lane> {
+        self.lanes.iter().find(|l| l.id == id)
+    }
+
+    /// IDs of the lanes, and any left on events (including trashed ones) by removed lanes.
+    pub fn lane_ids_in_use(&self) -> impl Iterator<Item = LaneId> + '_ {
+        let events = self
+            .events
+            .iter()
+            .chain(self.trash.iter().map(|t| &t.event));
+        self.lanes
+            .iter()
+            .map(|l| l.id)
+            .chain(events.filter_map(|e| e.lane))
+    }
+
+    /// Replaces the whole lane list (names and order) as one undo step. Events in a removed
+    /// lane move to the band for events without one.
+    pub fn set_lanes(&mut self, lanes: Vec<Lane>) {
+        if lanes == self.lanes {
+            return;
+        }
+        let before = std::mem::replace(&mut self.lanes, lanes.clone());
+        self.record(Command::SetLanes {
+            before,
+            after: lanes,
+        });
+    }
+
+    /// Folding a lane away only changes the view, so it isn't recorded for undo.
+    pub fn set_lane_collapsed(&mut self, id: LaneId, collapsed: bool) {
+        if let Some(lane) = self.lanes.iter_mut().find(|l| l.id == id) {
+            lane.collapsed = collapsed;
+        }
+    }
+
+    /// The colour an event is drawn in: its category's, or its own.
+    pub fn event_color(&self, event: &Event) -> Color32 {
+        event
+            .category
+            .and_then(|id| self.category(id))
+            .map_or(event.color, Category::color32)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// Reverts the most recent change. Returns `false` if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(command) = self.history.pop_undo() else {
+            return false;
+        };
+        match &command {
+            Command::Add(event) => {
+                self.take_event(event.id);
+            }
+            Command::Remove(entry) => {
+                if let Some(entry) = self.take_trashed(entry.event.id) {
+                    self.insert_sorted(entry.event);
+                }
+            }
+            Command::Edit { before, .. } => {
+                self.take_event(before.id);
+                self.insert_sorted(before.clone());
+            }
+            Command::Import(events) => {
+                for event in events {
+                    self.take_event(event.id);
+                }
+            }
+            Command::Restore(entry) => self.move_to_trash(entry.clone()),
+            Command::Purge(entries) => {
+                for entry in entries {
+                    self.insert_trashed(entry.clone());
+                }
+            }
+            Command::SetCategories { before, .. } => self.categories = before.clone(),
+            Command::SetLanes { before, .. } => self.lanes = before.clone(),
+        }
+        self.history.push_redo(command);
+        self.touch();
+        true
+    }
+
+    /// Reapplies the most recently undone change. Returns `false` if there was nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(command) = self.history.pop_redo() else {
+            return false;
+        };
+        match &command {
+            Command::Add(event) => self.insert_sorted(event.clone()),
+            Command::Remove(entry) => self.move_to_trash(entry.clone()),
+            Command::Edit { after, .. } => {
+                self.take_event(after.id);
+                self.insert_sorted(after.clone());
+            }
+            Command::Import(events) => {
+                for event in events {
+                    self.insert_sorted(event.clone());
+                }
+            }
+            Command::Restore(entry) => {
+                if let Some(entry) = self.take_trashed(entry.event.id) {
+                    self.insert_sorted(entry.event);
+                }
+            }
+            Command::Purge(entries) => {
+                for entry in entries {
+                    self.take_trashed(entry.event.id);
+                }
+            }
+            Command::SetCategories { after, .. } => self.categories = after.clone(),
+            Command::SetLanes { after, .. } => self.lanes = after.clone(),
+        }
+        self.history.push_undo(command);
+        self.touch();
+        true
     }
 
     pub fn events(&self) -> &[Event] {
         &self.events
     }
 
+    fn insert_sorted(&mut self, event: Event) {
+        // Insert after any equal timestamps so same-instant events keep their insertion order
+        let index = self
+            .events
+            .partition_point(|e| e.timestamp <= event.timestamp);
+        self.events.insert(index, event);
+    }
+
+    fn touch(&mut self) {
+        self.metadata.modified = date::now_seconds();
+    }
+
+    fn record(&mut self, command: Command) {
+        self.history.record(command);
+        self.touch();
+    }
+
+    /// Gives the event the next ID and inserts it, without recording an undo step.
+    fn insert_new(&mut self, mut event: Event) -> EventId {
+        event.id = EventId(self.next_id);
+        self.next_id += 1;
+        let id = event.id;
+        self.insert_sorted(event);
+        id
+    }
+
+    /// Removes the event without recording an undo step.
+    fn take_event(&mut self, id: EventId) -> Option<Event> {
+        let index = self.events.iter().position(|e| e.id == id)?;
+        Some(self.events.remove(index))
+    }
+
+    fn move_to_trash(&mut self, entry: TrashedEvent) {
+        self.take_event(entry.event.id);
+        self.insert_trashed(entry);
+    }
+
+    fn insert_trashed(&mut self, entry: TrashedEvent) {
+        let index = self.trash.partition_point(|t| t.deleted <= entry.deleted);
+        self.trash.insert(index, entry);
+    }
+
+    fn take_trashed(&mut self, id: EventId) -> Option<TrashedEvent> {
+        let index = self.trash.iter().position(|t| t.event.id == id)?;
+        Some(self.trash.remove(index))
+    }
+
+    fn serializable_events(&self) -> Vec<SerializableEvent> {
+        self.events.iter().map(|e| e.to_serializable()).collect()
+    }
+
+    fn serializable_trash(&self) -> Vec<SavedTrashEntry> {
+        self.trash
+            .iter()
+            .map(|t| SavedTrashEntry {
+                event: t.event.to_serializable(),
+                deleted: t.deleted,
+            })
+            .collect()
+    }
+
+    /// The events, trash, categories, layout and lanes, without metadata; two timelines with
+    /// the same content give the same string even if they were modified at different times.
+    pub fn content_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(&(
+            self.serializable_events(),
+            self.serializable_trash(),
+            &self.categories,
+            self.layout,
+            &self.lanes,
+        ))
+    }
+
     pub fn to_json(&self) -> Result<String, serde_json::Error> {
-        let serializable: Vec<SerializableEvent> =
-            self.events.iter().map(|e| e.to_serializable()).collect();
-        serde_json::to_string(&serializable)
+        serde_json::to_string(&SavedTimeline {
+            version: migrations::CURRENT_VERSION,
+            metadata: self.metadata.clone(),
+            events: self.serializable_events(),
+            trash: self.serializable_trash(),
+            categories: self.categories.clone(),
+            layout: self.layout,
+            lanes: self.lanes.clone(),
+        })
     }
 
+    /// Reads any format version, migrating older ones. Fails on events with impossible dates,
+    /// times or timezones, rather than computing nonsense timestamps for them.
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
-        let serializable: Vec<SerializableEvent> = serde_json::from_str(json)?;
-        let mut timeline = Timeline::new();
-        for s in serializable {
-            let mut event = Event::from_serializable(s.clone());
-            event.color =
-                Color32::from_rgba_unmultiplied(s.color[0], s.color[1], s.color[2], s.color[3]);
-            timeline.add_event(event);
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let saved: SavedTimeline =
+            if migrations::version_of(&value) == Some(migrations::CURRENT_VERSION) {
+                // Parse the text again rather than the value so errors keep their position
+                serde_json::from_str(json)?
+            } else {
+                // Bare event arrays (version 1) are still events as they are, so re-parsing the
+                // text finds the position of an error that parsing the migrated value loses
+                let is_event_list = value.is_array();
+                let upgraded = migrations::upgrade(value, date::now_seconds())?;
+                serde_json::from_value(upgraded).map_err(|err| {
+                    if is_event_list {
+                        serde_json::from_str::<Vec<SerializableEvent>>(json)
+                            .err()
+                            .unwrap_or(err)
+                    } else {
+                        err
+                    }
+                })?
+            };
+        let saved_events = saved
+            .events
+            .iter()
+            .chain(saved.trash.iter().map(|t| &t.event));
+        if let Some(message) = saved_events
+            .filter_map(SerializableEvent::date_error)
+            .next()
+        {
+            return Err(serde::de::Error::custom(message));
         }
+
+        let mut timeline = Self::from_saved(saved.events, saved.trash, saved.metadata);
+        timeline.categories = saved.categories;
+        timeline.layout = saved.layout;
+        timeline.lanes = saved.lanes;
         Ok(timeline)
     }
+
+    /// Best-effort load of damaged data: keeps every event that parses on its own (with valid
+    /// dates and times), even when the document as a whole doesn't. Also returns how many
+    /// events had to be skipped.
+    pub fn from_json_lenient(json: &str) -> (Self, usize) {
+        use serde_json::Value;
+
+        let mut metadata = None;
+        let mut trash = Vec::new();
+        let mut categories = None;
+        let mut layout = None;
+        let mut lanes = None;
+        let (events, skipped) = match serde_json::from_str::<Value>(json) {
+            Ok(value) => {
+                let value =
+                    migrations::upgrade(value.clone(), date::now_seconds()).unwrap_or(value);
+                let list = match value {
+                    Value::Array(list) => list,
+                    Value::Object(mut map) => {
+                        metadata = map
+                            .remove("metadata")
+                            .and_then(|m| serde_json::from_value(m).ok());
+                        categories = map
+                            .remove("categories")
+                            .and_then(|c| serde_json::from_value(c).ok());
+                        layout = map
+                            .remove("layout")
+                            .and_then(|l| serde_json::from_value(l).ok());
+                        lanes = map
+                            .remove("lanes")
+                            .and_then(|l| serde_json::from_value(l).ok());
+                        if let Some(Value::Array(entries)) = map.remove("trash") {
+                            trash = entries
+                                .into_iter()
+                                .filter_map(|v| serde_json::from_value(v).ok())
+                                .filter(|t: &SavedTrashEntry| t.event.date_error().is_none())
+                                .collect();
+                        }
+                        match map.remove("events") {
+                            Some(Value::Array(list)) => list,
+                            _ => Vec::new(),
+                        }
+                    }
+                    _ => Vec::new(),
+                };
+                let total = list.len();
+                let events: Vec<SerializableEvent> = list
+                    .into_iter()
+                    .filter_map(|v| serde_json::from_value(v).ok())
+                    .filter(|e: &SerializableEvent| e.date_error().is_none())
+                    .collect();
+                let skipped = total - events.len();
+                (events, skipped)
+            }
+            // Not even valid JSON (e.g. truncated): pick the event objects out of the text
+            Err(_) => {
+                let objects = recovery::event_objects(json);
+                let events: Vec<SerializableEvent> = objects
+                    .iter()
+                    .filter_map(|text| serde_json::from_str(text).ok())
+                    .filter(|e: &SerializableEvent| e.date_error().is_none())
+                    .collect();
+                let skipped = objects.len() - events.len();
+                (events, skipped)
+            }
+        };
+
+        let metadata = metadata.unwrap_or_else(|| Timeline::new().metadata);
+        let mut timeline = Self::from_saved(events, trash, metadata);
+        if let Some(categories) = categories {
+            timeline.categories = categories;
+        }
+        timeline.layout = layout.unwrap_or_default();
+        timeline.lanes = lanes.unwrap_or_default();
+        (timeline, skipped)
+    }
+
+    fn from_saved(
+        events: Vec<SerializableEvent>,
+        trash: Vec<SavedTrashEntry>,
+        metadata: TimelineMetadata,
+    ) -> Self {
+        let mut timeline = Timeline::new();
+        let max_id = events
+            .iter()
+            .chain(trash.iter().map(|t| &t.event))
+            .filter_map(|s| s.id)
+            .map(|id| id.0)
+            .max();
+        // IDs so high the counter could overflow (only a hand-edited file has them) are all
+        // replaced; they only need to be unique within the session
+        let renumber = max_id.is_some_and(|max| max > MAX_SAVED_ID);
+        timeline.next_id = match max_id {
+            Some(max) if !renumber => max + 1,
+            _ => 1,
+        };
+        let claim = |timeline: &mut Timeline, id: EventId| {
+            timeline.claim_id(if renumber { EventId(0) } else { id })
+        };
+
+        for s in events {
+            let mut event = Self::event_from_saved(s);
+            event.id = claim(&mut timeline, event.id);
+            timeline.insert_sorted(event);
+        }
+        for entry in trash {
+            let mut event = Self::event_from_saved(entry.event);
+            event.id = claim(&mut timeline, event.id);
+            timeline.insert_trashed(TrashedEvent {
+                event,
+                deleted: entry.deleted,
+            });
+        }
+        timeline.metadata = metadata;
+        timeline
+    }
+
+    /// Saved events keep their ID, except where it's missing (`EventId(0)`, from data saved
+    /// before IDs existed) or already taken; those get a fresh one.
+    fn claim_id(&mut self, saved: EventId) -> EventId {
+        let taken = self.events.iter().any(|e| e.id == saved)
+            || self.trash.iter().any(|t| t.event.id == saved);
+        if saved.0 == 0 || taken {
+            let id = EventId(self.next_id);
+            self.next_id += 1;
+            id
+        } else {
+            saved
+        }
+    }
+
+    fn event_from_saved(s: SerializableEvent) -> Event {
+        let saved_id = s.id;
+        let mut event = Event::from_serializable(s.clone());
+        event.color =
+            Color32::from_rgba_unmultiplied(s.color[0], s.color[1], s.color[2], s.color[3]);
+        event.id = saved_id.unwrap_or(EventId(0));
+        event
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(title: &str, day: &str, month: &str, year: &str) -> EventInput {
+        EventInput {
+            title: title.to_string(),
+            day: day.to_string(),
+            month: month.to_string(),
+            year: year.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn validate_builds_the_event() {
+        let event = input("Moon landing", "20", "7", "1969").validate().unwrap();
+        assert_eq!(event.title, "Moon landing");
+        assert_eq!(event.date(), Date::new(1969, 7, 20));
+        assert_eq!(
+            event.timestamp,
+            Date::new(1969, 7, 20).days_since_epoch() * date::SECONDS_PER_DAY
+        );
+    }
+
+    #[test]
+    fn validate_reports_every_problem() {
+        let errors = input(" ", "1", "13", "1969").validate().unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                ValidationError::EmptyTitle,
+                ValidationError::MonthOutOfRange("13".to_string()),
+            ]
+        );
+
+        let errors = input("Title", "31", "4", "2024").validate().unwrap_err();
+        assert_eq!(
+            errors,
+            vec![ValidationError::InvalidDay {
+                day: "31".to_string(),
+                month: 4,
+                year: 2024,
+            }]
+        );
+
+        // February 29 only exists in leap years
+        assert!(input("Leap", "29", "2", "2024").validate().is_ok());
+        assert!(input("Leap", "29", "2", "1900").validate().is_err());
+    }
+
+    #[test]
+    fn validate_time_and_time_zone() {
+        let mut form = input("Meeting", "1", "7", "2024");
+        form.time = "12:30".to_string();
+        form.timezone = "+02:00".to_string();
+        let event = form.validate().unwrap();
+        assert_eq!(event.precision, DatePrecision::Time);
+        assert_eq!(event.timezone, Some(TimeZone::Offset(120)));
+
+        form.time.clear();
+        assert_eq!(
+            form.validate().unwrap_err(),
+            vec![ValidationError::TimeZoneWithoutTime]
+        );
+
+        form.time = "25:00".to_string();
+        form.timezone = "Nowhere".to_string();
+        assert_eq!(
+            form.validate().unwrap_err(),
+            vec![
+                ValidationError::InvalidTime("25:00".to_string()),
+                ValidationError::UnknownTimeZone("Nowhere".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_spans() {
+        let mut form = input("Trip", "10", "6", "2024");
+        form.end_day = "12".to_string();
+        form.end_month = "6".to_string();
+        form.end_year = "2024".to_string();
+        let event = form.validate().unwrap();
+        assert!(event.is_span());
+        // The span runs to the end of its last day
+        assert_eq!(
+            event.end_timestamp,
+            Some(Date::new(2024, 6, 13).days_since_epoch() * date::SECONDS_PER_DAY)
+        );
+
+        // Ending on the start day is a one-day span
+        form.end_day = "10".to_string();
+        assert!(form.validate().is_ok());
+
+        form.end_day = "9".to_string();
+        assert_eq!(
+            form.validate().unwrap_err(),
+            vec![ValidationError::EndBeforeStart]
+        );
+
+        form.end_month = "13".to_string();
+        assert_eq!(
+            form.validate().unwrap_err(),
+            vec![ValidationError::InvalidEndDate(Box::new(
+                ValidationError::MonthOutOfRange("13".to_string())
+            ))]
+        );
+    }
+
+    #[test]
+    fn validate_partial_dates() {
+        let year = input("Founded", "", "", "1850").validate().unwrap();
+        assert_eq!(year.precision, DatePrecision::Year);
+        // Coarse dates sit in the middle of their period
+        let (first, next) = DatePrecision::Year.day_range(Date::new(1850, 1, 1));
+        assert_eq!(year.timestamp, (first + next) * date::SECONDS_PER_DAY / 2);
+        assert!(year.is_uncertain());
+
+        let month = input("Harvest", "", "9", "1850").validate().unwrap();
+        assert_eq!(month.precision, DatePrecision::Month);
+
+        let season = input("Drought", "", "Summer", "1850").validate().unwrap();
+        assert_eq!(season.precision, DatePrecision::Season);
+        assert_eq!(season.month, 6);
+
+        assert_eq!(
+            input("Drought", "3", "Summer", "1850")
+                .validate()
+                .unwrap_err(),
+            vec![ValidationError::DayWithoutMonth]
+        );
+        assert_eq!(
+            input("Drought", "3", "", "1850").validate().unwrap_err(),
+            vec![ValidationError::DayWithoutMonth]
+        );
+
+        let mut form = input("Founded", "", "", "1850");
+        form.time = "12:00".to_string();
+        assert_eq!(
+            form.validate().unwrap_err(),
+            vec![ValidationError::TimeWithoutDay]
+        );
+    }
+
+    #[test]
+    fn circa_widens_the_uncertainty() {
+        let mut form = input("Battle", "14", "10", "1066");
+        assert_eq!(form.validate().unwrap().uncertainty_seconds(), 0);
+        form.circa = true;
+        let event = form.validate().unwrap();
+        assert!(event.is_uncertain());
+        assert!(event.uncertainty_seconds() > 0);
+    }
+
+    fn saved_event(id: u64, day: u8, month: u8, year: i64) -> String {
+        format!(
+            r#"{{"id": {}, "title": "Event {}", "description": "", "day": {}, "month": {},
+                "year": {}, "image_path": null, "color": [100, 150, 255, 255]}}"#,
+            id, id, day, month, year
+        )
+    }
+
+    fn saved_timeline(events: &[String]) -> String {
+        format!(r#"[{}]"#, events.join(","))
+    }
+
+    #[test]
+    fn from_json_rejects_impossible_dates() {
+        let valid = saved_event(1, 15, 3, -43);
+        let timeline = Timeline::from_json(&saved_timeline(std::slice::from_ref(&valid))).unwrap();
+        assert_eq!(timeline.events()[0].date(), Date::new(-43, 3, 15));
+
+        for invalid in [
+            saved_event(2, 1, 13, 2000),
+            saved_event(2, 0, 1, 2000),
+            saved_event(2, 29, 2, 1900),
+            saved_event(2, 1, 1, date::MAX_YEAR_MAGNITUDE + 1),
+        ] {
+            let json = saved_timeline(&[valid.clone(), invalid.clone()]);
+            assert!(Timeline::from_json(&json).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn ids_survive_a_round_trip() {
+        let json = saved_timeline(&[saved_event(7, 1, 1, 2000), saved_event(3, 1, 1, 1990)]);
+        let mut timeline = Timeline::from_json(&json).unwrap();
+        let ids: Vec<EventId> = timeline.events().iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![EventId(3), EventId(7)]);
+
+        let added = timeline.add_event(Event::new(
+            "New".to_string(),
+            String::new(),
+            1,
+            1,
+            2010,
+            None,
+        ));
+        assert_eq!(added, EventId(8));
+
+        let reloaded = Timeline::from_json(&timeline.to_json().unwrap()).unwrap();
+        let reloaded_ids: Vec<EventId> = reloaded.events().iter().map(|e| e.id).collect();
+        assert_eq!(reloaded_ids, vec![EventId(3), EventId(7), EventId(8)]);
+    }
+
+    #[test]
+    fn overflowing_and_duplicate_ids_are_renumbered() {
+        let json = saved_timeline(&[
+            saved_event(u64::MAX, 1, 1, 2000),
+            saved_event(5, 1, 1, 1990),
+            saved_event(5, 1, 1, 1995),
+        ]);
+        let mut timeline = Timeline::from_json(&json).unwrap();
+        let mut ids: Vec<u64> = timeline.events().iter().map(|e| e.id.0).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 3);
+        assert!(ids.iter().all(|&id| id <= MAX_SAVED_ID));
+
+        let added = timeline.add_event(Event::new(
+            "New".to_string(),
+            String::new(),
+            1,
+            1,
+            2010,
+            None,
+        ));
+        assert!(!ids.contains(&added.0));
+    }
+
+    #[test]
+    fn from_event_round_trips_through_validate() {
+        let mut span = input("Reign", "", "Spring", "44 BCE");
+        span.end_year = "14 CE".to_string();
+        span.circa = true;
+        let mut timed = input("Meeting", "1", "7", "2024");
+        timed.time = "12:30:15".to_string();
+        timed.timezone = "Europe/Oslo".to_string();
+
+        for form in [span, timed, input("Impact", "", "", "66 Ma")] {
+            let event = form.validate().unwrap();
+            let prefilled = EventInput::from_event(&event);
+            let again = prefilled.validate().unwrap();
+            assert_eq!(again.timestamp, event.timestamp, "{:?}", prefilled);
+            assert_eq!(again.end_timestamp, event.end_timestamp);
+            assert_eq!(again.precision, event.precision);
+            assert_eq!(again.circa, event.circa);
+        }
+
+        // The year is shown the way it was typed, not as the astronomical -43
+        let event = input("Caesar", "15", "3", "44 BCE").validate().unwrap();
+        assert_eq!(EventInput::from_event(&event).year, "44 BCE");
+        assert_eq!(EventInput::from_event(&event).month, "3");
+    }
+
+    #[test]
+    fn lenient_loading_skips_broken_events() {
+        let json = saved_timeline(&[
+            saved_event(1, 1, 1, 2000),
+            saved_event(2, 1, 13, 2000),
+            r#"{"title": "No date"}"#.to_string(),
+            saved_event(3, 1, 1, 2010),
+        ]);
+        assert!(Timeline::from_json(&json).is_err());
+
+        let (timeline, skipped) = Timeline::from_json_lenient(&json);
+        assert_eq!(skipped, 2);
+        let ids: Vec<EventId> = timeline.events().iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![EventId(1), EventId(3)]);
+    }
+
+    #[test]
+    fn lenient_loading_recovers_truncated_files() {
+        let json = saved_timeline(&[saved_event(1, 1, 1, 2000), saved_event(2, 1, 1, 2010)]);
+        let truncated = &json[..json.len() - 20];
+        assert!(Timeline::from_json(truncated).is_err());
+
+        let (timeline, skipped) = Timeline::from_json_lenient(truncated);
+        assert_eq!(timeline.events().len(), 1);
+        assert_eq!(timeline.events()[0].id, EventId(1));
+        // The cut-off second event is reported as lost
+        assert_eq!(skipped, 1);
+    }
+
+    #[test]
+    fn errors_in_old_files_point_at_the_text() {
+        let json = format!(
+            "[\n{},\n{}\n]",
+            saved_event(1, 1, 1, 2000),
+            r#"{"title": 5}"#
+        );
+        let Err(error) = Timeline::from_json(&json) else {
+            panic!("loaded a file with a broken event");
+        };
+        assert!(error.line() > 1, "{}", error);
+    }
+
+    #[test]
+    fn undo_and_redo_edits() {
+        let mut timeline = Timeline::new();
+        assert!(!timeline.undo());
+
+        let id = timeline.add_event(input("First", "1", "1", "2000").validate().unwrap());
+        timeline.update_event(id, input("Renamed", "1", "1", "2001").validate().unwrap());
+        timeline.remove_event(id);
+        assert!(timeline.events().is_empty());
+        assert_eq!(timeline.trash().len(), 1);
+
+        assert!(timeline.undo());
+        assert_eq!(timeline.event(id).unwrap().title, "Renamed");
+        assert!(timeline.trash().is_empty());
+        assert!(timeline.undo());
+        assert_eq!(timeline.event(id).unwrap().title, "First");
+        assert_eq!(timeline.event(id).unwrap().year, 2000);
+        assert!(timeline.undo());
+        assert!(timeline.events().is_empty());
+        assert!(!timeline.can_undo());
+
+        assert!(timeline.redo());
+        assert!(timeline.redo());
+        assert_eq!(timeline.event(id).unwrap().title, "Renamed");
+        assert!(timeline.can_redo());
+
+        // A new change drops whatever could still be redone
+        timeline.add_event(input("Second", "1", "1", "2002").validate().unwrap());
+        assert!(!timeline.can_redo());
+        assert_eq!(timeline.events().len(), 2);
+    }
+
+    #[test]
+    fn lane_ids_left_on_events_stay_in_use() {
+        let mut timeline = Timeline::new();
+        let career = Lane {
+            id: LaneId(1),
+            name: "Career".to_string(),
+            collapsed: false,
+        };
+        timeline.set_lanes(vec![career.clone()]);
+        let mut form = input("First job", "1", "9", "2010");
+        form.lane = Some(career.id);
+        let id = timeline.add_event(form.validate().unwrap());
+
+        let reloaded = Timeline::from_json(&timeline.to_json().unwrap()).unwrap();
+        assert_eq!(reloaded.lanes(), [career]);
+        assert_eq!(reloaded.event(id).unwrap().lane, Some(LaneId(1)));
+
+        // A new lane mustn't reuse the ID of a removed one its events still carry
+        timeline.set_lanes(Vec::new());
+        assert_eq!(
+            crate::lane::next_id(timeline.lane_ids_in_use()),
+            Some(LaneId(2))
+        );
+    }
+
+    #[test]
+    fn from_json_rejects_impossible_times_and_zones() {
+        let with = |fields: &str| {
+            saved_event(1, 1, 7, 2024)
+                .replace("\"image_path\"", &format!("{}, \"image_path\"", fields))
+        };
+
+        let valid = with(r#""hour": 12, "minute": 30, "timezone": "Europe/Oslo""#);
+        let timeline = Timeline::from_json(&saved_timeline(&[valid])).unwrap();
+        let event = &timeline.events()[0];
+        assert_eq!(event.time, TimeOfDay::new(12, 30, 0));
+        assert_eq!(event.timezone, TimeZone::parse("Europe/Oslo"));
+
+        for invalid in [
+            with(r#""hour": 24"#),
+            with(r#""hour": 12, "minute": 60"#),
+            with(r#""hour": 12, "second": 60"#),
+            with(r#""minute": 30"#),
+            with(r#""hour": 12, "timezone": "Mars/Olympus""#),
+        ] {
+            let json = saved_timeline(&[saved_event(2, 1, 1, 2000), invalid.clone()]);
+            assert!(Timeline::from_json(&json).is_err(), "{}", invalid);
+
+            let (timeline, skipped) = Timeline::from_json_lenient(&json);
+            assert_eq!(skipped, 1, "{}", invalid);
+            assert_eq!(timeline.events().len(), 1);
+        }
+    }
+
+    #[test]
+    fn category_ids_left_on_events_stay_in_use() {
+        let mut timeline = Timeline::new();
+        let health = timeline.categories().last().unwrap().clone();
+        let mut form = input("Checkup", "1", "3", "2024");
+        form.category = Some(health.id);
+        let id = timeline.add_event(form.validate().unwrap());
+        timeline.remove_event(id);
+
+        let mut palette = timeline.categories().to_vec();
+        palette.retain(|c| c.id != health.id);
+        timeline.set_categories(palette);
+
+        // A new category mustn't reuse the ID its trashed event still carries
+        let next = crate::category::next_id(timeline.category_ids_in_use()).unwrap();
+        assert!(next.0 > health.id.0);
+    }
+
+    #[test]
+    fn undo_and_redo_trash_operations() {
+        let mut timeline = Timeline::new();
+        let first = timeline.add_event(input("First", "1", "1", "2000").validate().unwrap());
+        let second = timeline.add_event(input("Second", "1", "1", "2001").validate().unwrap());
+        timeline.remove_event(first);
+        timeline.remove_event(second);
+        // Sorted, since both were deleted within the same second
+        let trashed = |timeline: &Timeline| -> Vec<EventId> {
+            let mut ids: Vec<EventId> = timeline.trash().iter().map(|t| t.event.id).collect();
+            ids.sort();
+            ids
+        };
+
+        // Restore
+        assert!(timeline.restore_event(first));
+        assert!(!timeline.restore_event(first));
+        assert!(timeline.event(first).is_some());
+        assert!(timeline.undo());
+        assert!(timeline.event(first).is_none());
+        assert_eq!(trashed(&timeline), vec![first, second]);
+        assert!(timeline.redo());
+        assert!(timeline.event(first).is_some());
+        assert_eq!(trashed(&timeline), vec![second]);
+
+        // Purge
+        assert!(timeline.purge_event(second));
+        assert!(trashed(&timeline).is_empty());
+        assert!(timeline.undo());
+        assert_eq!(trashed(&timeline), vec![second]);
+        assert!(timeline.redo());
+        assert!(trashed(&timeline).is_empty());
+        assert!(timeline.event(second).is_none());
+
+        // Empty the trash
+        timeline.undo();
+        timeline.remove_event(first);
+        assert_eq!(trashed(&timeline).len(), 2);
+        timeline.empty_trash();
+        assert!(trashed(&timeline).is_empty());
+        assert!(timeline.undo());
+        assert_eq!(trashed(&timeline).len(), 2);
+        assert!(timeline.redo());
+        assert!(trashed(&timeline).is_empty());
+        assert!(timeline.events().is_empty());
+    }
 }